@@ -0,0 +1,627 @@
+//! Ballots, the openings drawn at random at the start of competitive English
+//! draughts games. Playing from a drawn opening stops either side relying on
+//! a prepared line, and gives engine matches a spread of different games.
+
+use std::{fmt, str::FromStr, sync::OnceLock};
+
+use anyhow::{anyhow, Result};
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{
+    board::Board,
+    difficulty::AiSettings,
+    game::GameMode,
+    player::Player,
+    r#move::{Move, Position},
+    result::GameResult,
+    rules::Variant,
+};
+
+/// The three-move deck in ballot order, with moves written as square numbers.
+/// Every opening is in the deck apart from those which simply give away a
+/// man, numbered in the order of their moves. Openings ending in a capture
+/// leave Black a man ahead until White recaptures on the next move.
+///
+/// This isn't the ACF's list of 156 ballots, so the numbers don't match its
+/// published numbering.
+const THREE_MOVE_OPENINGS: [&str; 246] = [
+    "9-13 21-17 5-9",
+    "9-13 21-17 6-9",
+    "9-13 21-17 10-14",
+    "9-13 21-17 10-15",
+    "9-13 21-17 11-15",
+    "9-13 21-17 11-16",
+    "9-13 21-17 12-16",
+    "9-13 22-17 13x22",
+    "9-13 22-18 6-9",
+    "9-13 22-18 10-14",
+    "9-13 22-18 10-15",
+    "9-13 22-18 11-15",
+    "9-13 22-18 11-16",
+    "9-13 22-18 12-16",
+    "9-13 22-18 13-17",
+    "9-13 23-18 5-9",
+    "9-13 23-18 6-9",
+    "9-13 23-18 10-14",
+    "9-13 23-18 10-15",
+    "9-13 23-18 11-15",
+    "9-13 23-18 11-16",
+    "9-13 23-18 12-16",
+    "9-13 23-19 5-9",
+    "9-13 23-19 6-9",
+    "9-13 23-19 10-14",
+    "9-13 23-19 10-15",
+    "9-13 23-19 11-15",
+    "9-13 23-19 11-16",
+    "9-13 24-19 5-9",
+    "9-13 24-19 6-9",
+    "9-13 24-19 10-14",
+    "9-13 24-19 10-15",
+    "9-13 24-19 11-15",
+    "9-13 24-19 11-16",
+    "9-13 24-20 5-9",
+    "9-13 24-20 6-9",
+    "9-13 24-20 10-14",
+    "9-13 24-20 10-15",
+    "9-13 24-20 11-15",
+    "9-13 24-20 11-16",
+    "9-13 24-20 12-16",
+    "9-14 22-17 5-9",
+    "9-14 22-17 6-9",
+    "9-14 22-17 10-15",
+    "9-14 22-17 11-15",
+    "9-14 22-17 11-16",
+    "9-14 22-18 5-9",
+    "9-14 22-18 6-9",
+    "9-14 22-18 10-15",
+    "9-14 22-18 11-15",
+    "9-14 22-18 11-16",
+    "9-14 22-18 12-16",
+    "9-14 22-18 14-17",
+    "9-14 23-18 14x23",
+    "9-14 23-19 5-9",
+    "9-14 23-19 6-9",
+    "9-14 23-19 10-15",
+    "9-14 23-19 11-15",
+    "9-14 23-19 11-16",
+    "9-14 23-19 14-18",
+    "9-14 24-19 5-9",
+    "9-14 24-19 6-9",
+    "9-14 24-19 10-15",
+    "9-14 24-19 11-15",
+    "9-14 24-19 11-16",
+    "9-14 24-20 5-9",
+    "9-14 24-20 6-9",
+    "9-14 24-20 10-15",
+    "9-14 24-20 11-15",
+    "9-14 24-20 11-16",
+    "10-14 22-17 6-10",
+    "10-14 22-17 7-10",
+    "10-14 22-17 9-13",
+    "10-14 22-17 11-15",
+    "10-14 22-17 11-16",
+    "10-14 22-17 14-18",
+    "10-14 22-18 6-10",
+    "10-14 22-18 7-10",
+    "10-14 22-18 9-13",
+    "10-14 22-18 11-15",
+    "10-14 22-18 11-16",
+    "10-14 22-18 12-16",
+    "10-14 23-18 14x23",
+    "10-14 23-19 6-10",
+    "10-14 23-19 7-10",
+    "10-14 23-19 9-13",
+    "10-14 23-19 11-15",
+    "10-14 23-19 11-16",
+    "10-14 23-19 14-18",
+    "10-14 24-19 6-10",
+    "10-14 24-19 7-10",
+    "10-14 24-19 9-13",
+    "10-14 24-19 11-15",
+    "10-14 24-19 11-16",
+    "10-14 24-19 14-18",
+    "10-14 24-20 6-10",
+    "10-14 24-20 7-10",
+    "10-14 24-20 9-13",
+    "10-14 24-20 11-15",
+    "10-14 24-20 11-16",
+    "10-14 24-20 14-18",
+    "10-15 21-17 6-10",
+    "10-15 21-17 7-10",
+    "10-15 21-17 9-13",
+    "10-15 21-17 9-14",
+    "10-15 21-17 11-16",
+    "10-15 21-17 15-18",
+    "10-15 22-17 6-10",
+    "10-15 22-17 7-10",
+    "10-15 22-17 9-13",
+    "10-15 22-17 9-14",
+    "10-15 22-17 11-16",
+    "10-15 22-17 15-18",
+    "10-15 22-17 15-19",
+    "10-15 22-18 15x22",
+    "10-15 23-18 6-10",
+    "10-15 23-18 7-10",
+    "10-15 23-18 9-13",
+    "10-15 23-18 9-14",
+    "10-15 23-18 11-16",
+    "10-15 23-18 12-16",
+    "10-15 23-19 6-10",
+    "10-15 23-19 7-10",
+    "10-15 23-19 9-13",
+    "10-15 23-19 9-14",
+    "10-15 23-19 11-16",
+    "10-15 23-19 15-18",
+    "10-15 24-19 15x24",
+    "10-15 24-20 6-10",
+    "10-15 24-20 7-10",
+    "10-15 24-20 9-13",
+    "10-15 24-20 9-14",
+    "10-15 24-20 11-16",
+    "10-15 24-20 12-16",
+    "10-15 24-20 15-18",
+    "10-15 24-20 15-19",
+    "11-15 21-17 7-11",
+    "11-15 21-17 8-11",
+    "11-15 21-17 9-13",
+    "11-15 21-17 9-14",
+    "11-15 21-17 10-14",
+    "11-15 21-17 15-19",
+    "11-15 22-17 7-11",
+    "11-15 22-17 8-11",
+    "11-15 22-17 9-13",
+    "11-15 22-17 9-14",
+    "11-15 22-17 10-14",
+    "11-15 22-17 15-18",
+    "11-15 22-17 15-19",
+    "11-15 22-18 15x22",
+    "11-15 23-18 7-11",
+    "11-15 23-18 8-11",
+    "11-15 23-18 9-13",
+    "11-15 23-18 9-14",
+    "11-15 23-18 10-14",
+    "11-15 23-18 12-16",
+    "11-15 23-18 15-19",
+    "11-15 23-19 7-11",
+    "11-15 23-19 8-11",
+    "11-15 23-19 9-13",
+    "11-15 23-19 9-14",
+    "11-15 23-19 10-14",
+    "11-15 24-19 15x24",
+    "11-15 24-20 7-11",
+    "11-15 24-20 8-11",
+    "11-15 24-20 9-13",
+    "11-15 24-20 9-14",
+    "11-15 24-20 10-14",
+    "11-15 24-20 12-16",
+    "11-15 24-20 15-18",
+    "11-15 24-20 15-19",
+    "11-16 21-17 7-11",
+    "11-16 21-17 8-11",
+    "11-16 21-17 9-13",
+    "11-16 21-17 9-14",
+    "11-16 21-17 10-14",
+    "11-16 21-17 10-15",
+    "11-16 21-17 16-19",
+    "11-16 21-17 16-20",
+    "11-16 22-17 7-11",
+    "11-16 22-17 8-11",
+    "11-16 22-17 9-13",
+    "11-16 22-17 9-14",
+    "11-16 22-17 10-14",
+    "11-16 22-17 10-15",
+    "11-16 22-17 16-19",
+    "11-16 22-17 16-20",
+    "11-16 22-18 7-11",
+    "11-16 22-18 8-11",
+    "11-16 22-18 9-13",
+    "11-16 22-18 9-14",
+    "11-16 22-18 10-14",
+    "11-16 22-18 10-15",
+    "11-16 22-18 16-19",
+    "11-16 22-18 16-20",
+    "11-16 23-18 7-11",
+    "11-16 23-18 8-11",
+    "11-16 23-18 9-13",
+    "11-16 23-18 9-14",
+    "11-16 23-18 10-14",
+    "11-16 23-18 10-15",
+    "11-16 23-18 16-19",
+    "11-16 23-18 16-20",
+    "11-16 23-19 16x23",
+    "11-16 24-19 7-11",
+    "11-16 24-19 8-11",
+    "11-16 24-19 9-13",
+    "11-16 24-19 9-14",
+    "11-16 24-19 10-14",
+    "11-16 24-19 10-15",
+    "11-16 24-19 16-20",
+    "11-16 24-20 7-11",
+    "11-16 24-20 8-11",
+    "11-16 24-20 9-13",
+    "11-16 24-20 9-14",
+    "11-16 24-20 10-14",
+    "11-16 24-20 10-15",
+    "11-16 24-20 16-19",
+    "12-16 21-17 8-12",
+    "12-16 21-17 9-13",
+    "12-16 21-17 9-14",
+    "12-16 21-17 16-19",
+    "12-16 21-17 16-20",
+    "12-16 22-17 8-12",
+    "12-16 22-17 16-19",
+    "12-16 22-17 16-20",
+    "12-16 22-18 8-12",
+    "12-16 22-18 9-13",
+    "12-16 22-18 9-14",
+    "12-16 22-18 10-14",
+    "12-16 22-18 16-19",
+    "12-16 22-18 16-20",
+    "12-16 23-18 8-12",
+    "12-16 23-18 9-13",
+    "12-16 23-18 9-14",
+    "12-16 23-18 10-15",
+    "12-16 23-18 11-15",
+    "12-16 23-18 16-19",
+    "12-16 23-18 16-20",
+    "12-16 23-19 16x23",
+    "12-16 24-19 8-12",
+    "12-16 24-19 16-20",
+    "12-16 24-20 8-12",
+    "12-16 24-20 9-13",
+    "12-16 24-20 10-15",
+    "12-16 24-20 11-15",
+];
+
+/// A collection of ballots to draw from
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Deck {
+    /// The first three moves of the game are drawn
+    #[default]
+    ThreeMove,
+    /// One man is taken off the board from each side before the game starts
+    ElevenMan,
+}
+
+impl Deck {
+    pub const ALL: [Deck; 2] = [Deck::ThreeMove, Deck::ElevenMan];
+
+    /// Every ballot in the deck. The deck is built the first time it is
+    /// needed, and kept for the rest of the program.
+    pub fn ballots(&self) -> &'static [Ballot] {
+        static THREE_MOVE: OnceLock<Vec<Ballot>> = OnceLock::new();
+        static ELEVEN_MAN: OnceLock<Vec<Ballot>> = OnceLock::new();
+
+        match self {
+            Deck::ThreeMove => THREE_MOVE.get_or_init(three_move_ballots),
+            Deck::ElevenMan => ELEVEN_MAN.get_or_init(eleven_man_ballots),
+        }
+    }
+
+    /// Get a ballot by its number, counting from 1
+    pub fn ballot(&self, number: usize) -> Result<&'static Ballot> {
+        let ballots = self.ballots();
+        number
+            .checked_sub(1)
+            .and_then(|idx| ballots.get(idx))
+            .ok_or_else(|| {
+                anyhow!(
+                    "There is no ballot {number}, the {self} deck has {} ballots",
+                    ballots.len()
+                )
+            })
+    }
+
+    pub fn draw<R: Rng>(&self, rng: &mut R) -> &'static Ballot {
+        // SAFETY: neither deck is ever empty
+        self.ballots().choose(rng).unwrap()
+    }
+}
+
+impl fmt::Display for Deck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Deck::ThreeMove => "three-move",
+            Deck::ElevenMan => "eleven-man",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl FromStr for Deck {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "three-move" | "3-move" => Ok(Deck::ThreeMove),
+            "eleven-man" | "11-man" => Ok(Deck::ElevenMan),
+            _ => Err(anyhow!("Unknown ballot deck {s}")),
+        }
+    }
+}
+
+/// An opening drawn from a deck
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ballot {
+    deck: Deck,
+    /// The position of the ballot in its deck, counting from 1
+    number: usize,
+    /// Men taken off the board before the game starts
+    removed: Vec<Position>,
+    /// Moves played from the start of the game
+    moves: Vec<Move>,
+}
+
+impl Ballot {
+    pub fn deck(&self) -> Deck {
+        self.deck
+    }
+
+    pub fn number(&self) -> usize {
+        self.number
+    }
+
+    pub fn removed(&self) -> &[Position] {
+        &self.removed
+    }
+
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    /// The board with any men removed, before the ballots moves are played
+    pub fn setup(&self) -> Board {
+        let mut board = Board::new();
+        for pos in &self.removed {
+            board[*pos].leave();
+        }
+        board
+    }
+
+    /// The board once the ballot has been set up and its moves played
+    pub fn board(&self) -> Board {
+        let mut board = self.setup();
+        let mut player = board.rules().first_player();
+        for this_move in &self.moves {
+            board.apply_move(player, this_move);
+            player = !player;
+        }
+        board
+    }
+
+    pub fn player_to_move(&self) -> Player {
+        Board::new().player_for_turn(self.moves.len())
+    }
+}
+
+impl fmt::Display for Ballot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ballot {}:", self.deck, self.number)?;
+        if !self.removed.is_empty() {
+            let removed: Vec<String> = self.removed.iter().map(|pos| pos.to_string()).collect();
+            write!(f, " without {}", removed.join(" and "))?;
+        }
+        for this_move in &self.moves {
+            write!(f, " {}", this_move.notation())?;
+        }
+        Ok(())
+    }
+}
+
+/// Set up the three-move deck from its table of openings
+fn three_move_ballots() -> Vec<Ballot> {
+    THREE_MOVE_OPENINGS
+        .iter()
+        .enumerate()
+        .map(|(idx, opening)| {
+            let mut board = Board::with_variant(Variant::English);
+            let mut player = board.rules().first_player();
+            let moves = opening
+                .split_whitespace()
+                .map(|text| {
+                    // SAFETY: every opening in the table is checked by the tests
+                    let this_move = board.parse_move(player, text).unwrap();
+                    board.apply_move(player, &this_move);
+                    player = !player;
+                    this_move
+                })
+                .collect();
+
+            Ballot {
+                deck: Deck::ThreeMove,
+                number: idx + 1,
+                removed: Vec::new(),
+                moves,
+            }
+        })
+        .collect()
+}
+
+/// Every way of taking one man away from each side
+fn eleven_man_ballots() -> Vec<Ballot> {
+    let board = Board::with_variant(Variant::English);
+    let men = |player| {
+        board
+            .board()
+            .iter()
+            .enumerate()
+            .filter(move |(_, tile)| tile.is_occupied_by(player))
+            .map(|(idx, _)| board.position(idx))
+    };
+
+    men(Player::Black)
+        .flat_map(|black| men(Player::White).map(move |white| vec![black, white]))
+        .enumerate()
+        .map(|(idx, removed)| Ballot {
+            deck: Deck::ElevenMan,
+            number: idx + 1,
+            removed,
+            moves: Vec::new(),
+        })
+        .collect()
+}
+
+/// One game of a match between two engines
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pairing {
+    pub ballot: &'static Ballot,
+    /// The colour played by the first engine
+    pub first_engine: Player,
+}
+
+impl Pairing {
+    /// The game mode for this game, with `first` and `second` on the right
+    /// sides of the board
+    pub fn mode(&self, first: AiSettings, second: AiSettings) -> GameMode {
+        if self.ballot.board().rules().first_player() == self.first_engine {
+            GameMode::AiVsAi(first, second)
+        } else {
+            GameMode::AiVsAi(second, first)
+        }
+    }
+
+    /// The points the first engine scored in this game
+    pub fn score(&self, result: &GameResult) -> f64 {
+        result.points(self.first_engine)
+    }
+}
+
+/// Play each ballot twice with the engines swapping colours, so neither
+/// engine gains from being given the better side of an opening
+pub fn pair_colours(ballots: &[&'static Ballot]) -> Vec<Pairing> {
+    ballots
+        .iter()
+        .flat_map(|ballot| {
+            [Player::Black, Player::White].map(|first_engine| Pairing {
+                ballot,
+                first_engine,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::{
+        difficulty::{AiSettings, Difficulty},
+        game::GameMode,
+        player::Player,
+        r#move::Notation,
+        result::{DrawReason, GameResult, WinReason},
+        rules::Variant,
+    };
+
+    use super::{pair_colours, Deck, THREE_MOVE_OPENINGS};
+
+    #[test]
+    fn test_three_move_deck() {
+        let ballots = Deck::ThreeMove.ballots();
+        assert_eq!(ballots.len(), THREE_MOVE_OPENINGS.len());
+
+        // Giving away a man for nothing isn't a fair opening
+        assert!(!ballots
+            .iter()
+            .any(|ballot| ballot.to_string().ends_with(": a3-b4 b6-a5 b4-c5")));
+
+        for (idx, ballot) in ballots.iter().enumerate() {
+            assert_eq!(ballot.number(), idx + 1);
+            assert_eq!(ballot.moves().len(), 3);
+
+            // Every opening in the table is made of legal moves
+            let moves: Vec<String> = ballot
+                .moves()
+                .iter()
+                .map(|m| m.display(Notation::Numeric, Variant::English).to_string())
+                .collect();
+            assert_eq!(moves.join(" "), THREE_MOVE_OPENINGS[idx]);
+            assert_eq!(ballot.player_to_move(), Player::White);
+
+            // An opening ending in a capture leaves Black a man ahead, but
+            // White has to recapture straight away, which levels the material
+            let board = ballot.board();
+            let [black, white] =
+                [Player::Black, Player::White].map(|p| board.get_remaining_peices(p));
+            let mut boards = vec![board];
+            if black > white {
+                boards = board
+                    .get_legal_moves(Player::White)
+                    .iter()
+                    .map(|recapture| {
+                        assert!(recapture.is_capture(), "{ballot}");
+                        let mut board = board;
+                        board.apply_move(Player::White, recapture);
+                        board
+                    })
+                    .collect();
+            }
+            for board in boards {
+                let black = board.get_remaining_peices(Player::Black);
+                assert_eq!(black, board.get_remaining_peices(Player::White), "{ballot}");
+            }
+        }
+
+        let ballot = Deck::ThreeMove.ballot(1).unwrap();
+        assert_eq!(ballot.to_string(), "three-move ballot 1: g3-h4 h6-g5 h2-g3");
+        assert!(Deck::ThreeMove.ballot(0).is_err());
+        assert!(Deck::ThreeMove.ballot(ballots.len() + 1).is_err());
+    }
+
+    #[test]
+    fn test_eleven_man_deck() {
+        let ballots = Deck::ElevenMan.ballots();
+        assert_eq!(ballots.len(), 144);
+
+        let ballot = Deck::ElevenMan.ballot(144).unwrap();
+        assert_eq!(ballot.player_to_move(), Player::Black);
+        assert_eq!(ballot.board().get_remaining_peices(Player::Black), 11);
+        assert_eq!(ballot.board().get_remaining_peices(Player::White), 11);
+        assert_eq!(
+            ballot.to_string(),
+            "eleven-man ballot 144: without g3 and h8"
+        );
+
+        let mut rng = StdRng::seed_from_u64(7);
+        assert_eq!(Deck::ElevenMan.draw(&mut rng).deck(), Deck::ElevenMan);
+        assert_eq!("11-man".parse::<Deck>().unwrap(), Deck::ElevenMan);
+        assert!("4-move".parse::<Deck>().is_err());
+    }
+
+    #[test]
+    fn test_pair_colours() {
+        let ballots = [
+            Deck::ElevenMan.ballot(1).unwrap(),
+            Deck::ElevenMan.ballot(2).unwrap(),
+        ];
+        let pairings = pair_colours(&ballots);
+        assert_eq!(pairings.len(), 4);
+
+        // Each ballot is played once with each engine as black
+        for (ballot, games) in ballots.iter().zip(pairings.chunks(2)) {
+            assert!(games.iter().all(|game| game.ballot == *ballot));
+            assert_eq!(games[0].first_engine, Player::Black);
+            assert_eq!(games[1].first_engine, Player::White);
+        }
+
+        let strong = AiSettings {
+            difficulty: Difficulty::Expert,
+            ..AiSettings::default()
+        };
+        let weak = AiSettings::default();
+        assert!(matches!(pairings[0].mode(strong, weak), GameMode::AiVsAi(a, _) if a == strong));
+        assert!(matches!(pairings[1].mode(strong, weak), GameMode::AiVsAi(_, a) if a == strong));
+
+        // Scores are from the first engines point of view, whichever side it played
+        let black_wins = GameResult::Win {
+            winner: Player::Black,
+            reason: WinReason::NoPeices,
+        };
+        assert_eq!(pairings[0].score(&black_wins), 1.0);
+        assert_eq!(pairings[1].score(&black_wins), 0.0);
+        assert_eq!(
+            pairings[1].score(&GameResult::Draw(DrawReason::Repetition)),
+            0.5
+        );
+    }
+}
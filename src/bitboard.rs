@@ -0,0 +1,483 @@
+//! A 32 square bitboard used to generate moves quickly for English draughts.
+//!
+//! Only the playable tiles of an 8x8 board are stored. Square `n` is bit `n`,
+//! with four squares to each row, so square `4 * y + x / 2` holds the tile at
+//! `(x, y)`. Moving one tile diagonally is then a shift by 3, 4 or 5 bits
+//! depending on whether the peice starts on an even or odd row.
+
+use std::fmt;
+
+use crate::board::Board;
+use crate::player::Player;
+use crate::r#move::{Move, Position};
+use crate::rules::{CapturePriority, CapturePromotion, RuleSet};
+use crate::tile::{Tile, TileKind};
+
+/// Squares on rows 0, 2, 4 and 6
+const EVEN_ROWS: u32 = 0x0F0F_0F0F;
+/// Squares on rows 1, 3, 5 and 7
+const ODD_ROWS: u32 = 0xF0F0_F0F0;
+/// The first square of each row
+const FIRST_COLUMN: u32 = 0x1111_1111;
+/// The last square of each row
+const LAST_COLUMN: u32 = 0x8888_8888;
+/// The rows Black and White promote on
+const BLACK_PROMOTION_ROW: u32 = 0xF000_0000;
+const WHITE_PROMOTION_ROW: u32 = 0x0000_000F;
+
+/// The longest possible move, a capture of every opposing peice
+const MAX_PATH: usize = 13;
+
+/// Move every bit in `squares` one tile along a diagonal. Directions are
+/// numbered the same way as `king_moves::DIRECTIONS`.
+#[inline]
+pub(crate) fn step(squares: u32, direction: usize) -> u32 {
+    match direction {
+        0 => ((squares & EVEN_ROWS & !FIRST_COLUMN) >> 5) | ((squares & ODD_ROWS) >> 4),
+        1 => ((squares & EVEN_ROWS) >> 4) | ((squares & ODD_ROWS & !LAST_COLUMN) >> 3),
+        2 => ((squares & EVEN_ROWS & !FIRST_COLUMN) << 3) | ((squares & ODD_ROWS) << 4),
+        3 => ((squares & EVEN_ROWS) << 4) | ((squares & ODD_ROWS & !LAST_COLUMN) << 5),
+        _ => unreachable!(),
+    }
+}
+
+/// The directions men of each player move in
+pub(crate) fn forward_directions(player: Player) -> [usize; 2] {
+    match player {
+        Player::Black => [2, 3],
+        Player::White => [0, 1],
+    }
+}
+
+/// The squares a players men are crowned on
+pub(crate) fn promotion_row(player: Player) -> u32 {
+    match player {
+        Player::Black => BLACK_PROMOTION_ROW,
+        Player::White => WHITE_PROMOTION_ROW,
+    }
+}
+
+/// Convert a square number to an index in an 8x8 board
+pub fn square_to_idx(square: usize) -> usize {
+    let y = square / 4;
+    let x = 2 * (square % 4) + y % 2;
+    y * 8 + x
+}
+
+/// Convert an index in an 8x8 board to a square number, returning `None` for
+/// tiles which cannot be played on
+pub fn idx_to_square(idx: usize) -> Option<usize> {
+    let (x, y) = (idx % 8, idx / 8);
+    if (x + y).is_multiple_of(2) {
+        Some(4 * y + x / 2)
+    } else {
+        None
+    }
+}
+
+/// A move on a bitboard, stored as the squares visited and a mask of the
+/// squares captured
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct BitMove {
+    path: [u8; MAX_PATH],
+    len: u8,
+    captured: u32,
+}
+
+impl BitMove {
+    fn quiet(from: u32, to: u32) -> Self {
+        let mut path = [0; MAX_PATH];
+        path[0] = from as u8;
+        path[1] = to as u8;
+        Self {
+            path,
+            len: 2,
+            captured: 0,
+        }
+    }
+
+    pub fn from(&self) -> usize {
+        self.path[0] as usize
+    }
+
+    pub fn to(&self) -> usize {
+        self.path[self.len as usize - 1] as usize
+    }
+
+    /// Every square visited during the move
+    pub fn path(&self) -> &[u8] {
+        &self.path[..self.len as usize]
+    }
+
+    pub fn captured(&self) -> u32 {
+        self.captured
+    }
+
+    pub fn is_capture(&self) -> bool {
+        self.captured != 0
+    }
+
+    /// Convert the move into one which can be played on a `Board`
+    pub fn as_move(&self) -> Move {
+        let position = |square: u32| Position::from_idx(square_to_idx(square as usize));
+
+        let path = self.path().iter().map(|sq| position(*sq as u32)).collect();
+        let mut captured = Vec::new();
+        // Captured peices are listed in the order they are jumped
+        for window in self.path().windows(2) {
+            let (from, to) = (window[0] as u32, window[1] as u32);
+            let between = (0..4)
+                .map(|dir| step(1 << from, dir) & step(1 << to, 3 - dir))
+                .find(|mask| *mask != 0)
+                .unwrap_or(0);
+            if between != 0 && self.captured & between != 0 {
+                captured.push(position(between.trailing_zeros()));
+            }
+        }
+
+        Move::chain(path, captured)
+    }
+}
+
+/// The peices on an English draughts board, one bit per playable square
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Bitboard {
+    pub black: u32,
+    pub white: u32,
+    pub kings: u32,
+}
+
+impl Bitboard {
+    /// Test if the bitboard move generator can play a set of rules. It only
+    /// handles 8x8 boards with short kings, men which capture forwards, and
+    /// a free choice of capture.
+    pub fn supports(rules: &dyn RuleSet) -> bool {
+        rules.board_size() == 8
+            && rules.is_playable(0, 0)
+            && !rules.flying_kings()
+            && !rules.men_capture_backwards()
+            && rules.men_capture_kings()
+            && rules.capture_promotion() == CapturePromotion::EndsMove
+            && rules.capture_priority() == CapturePriority::Free
+    }
+
+    /// Convert a board, or return `None` if the bitboard can't hold it
+    /// exactly. Boards set up by hand may have peices on tiles which can't be
+    /// played on, and these have no square on the bitboard.
+    pub fn try_from_board(board: &Board) -> Option<Self> {
+        if !Self::supports(board.rules()) {
+            return None;
+        }
+
+        let bitboard = Self::from_board(board);
+        let peices = board.board().iter().filter(|tile| !tile.is_empty()).count();
+        let bits = (bitboard.black | bitboard.white).count_ones() as usize;
+        (bits == peices).then_some(bitboard)
+    }
+
+    /// Convert a board, skipping any peices on tiles which can't be played on
+    pub fn from_board(board: &Board) -> Self {
+        let mut bitboard = Self::default();
+        for (idx, tile) in board.board().iter().enumerate() {
+            if let Some(square) = idx_to_square(idx) {
+                bitboard.set(square, tile);
+            }
+        }
+        bitboard
+    }
+
+    /// Change a single square to hold the peice on `tile`, if any
+    pub fn set(&mut self, square: usize, tile: &Tile) {
+        let bit = 1 << square;
+        self.black &= !bit;
+        self.white &= !bit;
+        self.kings &= !bit;
+
+        match tile.occupied_by {
+            Some(Player::Black) => self.black |= bit,
+            Some(Player::White) => self.white |= bit,
+            None => return,
+        }
+
+        if tile.kind() == TileKind::King {
+            self.kings |= bit;
+        }
+    }
+
+    /// Write the peices back into a board's tiles
+    pub fn write_to(&self, board: &mut Board) {
+        for tile in board.board_mut().iter_mut() {
+            *tile = Tile::empty();
+        }
+
+        for square in 0..32 {
+            let bit = 1 << square;
+            let tile = &mut board.board_mut()[square_to_idx(square)];
+            if self.black & bit != 0 {
+                tile.take_ownership(Player::Black);
+            } else if self.white & bit != 0 {
+                tile.take_ownership(Player::White);
+            } else {
+                continue;
+            }
+
+            if self.kings & bit != 0 {
+                tile.promote();
+            }
+        }
+    }
+
+    pub fn peices(&self, player: Player) -> u32 {
+        match player {
+            Player::Black => self.black,
+            Player::White => self.white,
+        }
+    }
+
+    pub fn empty(&self) -> u32 {
+        !(self.black | self.white)
+    }
+
+    /// The same position seen from the other side of the board, with the
+    /// colours of the peices swapped
+    pub fn flipped(&self) -> Self {
+        // Reversing the squares turns the board around
+        Self {
+            black: self.white.reverse_bits(),
+            white: self.black.reverse_bits(),
+            kings: self.kings.reverse_bits(),
+        }
+    }
+
+    /// Get every peice belonging to `player` which is able to capture
+    pub fn jumpers(&self, player: Player) -> u32 {
+        let enemies = self.peices(!player);
+        let empty = self.empty();
+        let men = self.peices(player) & !self.kings;
+        let kings = self.peices(player) & self.kings;
+
+        let mut jumpers = 0;
+        for direction in 0..4 {
+            // Walk backwards from every empty square over an enemy peice
+            let reverse = 3 - direction;
+            let can_jump = step(step(empty, reverse) & enemies, reverse);
+            jumpers |= can_jump & kings;
+            if forward_directions(player).contains(&direction) {
+                jumpers |= can_jump & men;
+            }
+        }
+        jumpers
+    }
+
+    /// Get every legal move for `player`, only returning captures if one is
+    /// available
+    pub fn legal_moves(&self, player: Player) -> Vec<BitMove> {
+        let mut moves = Vec::new();
+        let mut jumpers = self.jumpers(player);
+
+        if jumpers != 0 {
+            while jumpers != 0 {
+                let from = jumpers.trailing_zeros();
+                jumpers &= jumpers - 1;
+
+                let mut path = [0; MAX_PATH];
+                path[0] = from as u8;
+                let is_king = self.kings & (1 << from) != 0;
+                self.extend_jumps(player, is_king, &mut path, 1, 0, &mut moves);
+            }
+            return moves;
+        }
+
+        let empty = self.empty();
+        let mut peices = self.peices(player);
+        while peices != 0 {
+            let from = peices.trailing_zeros();
+            peices &= peices - 1;
+
+            let bit = 1 << from;
+            for direction in 0..4 {
+                let is_forwards = forward_directions(player).contains(&direction);
+                if !is_forwards && self.kings & bit == 0 {
+                    continue;
+                }
+
+                let target = step(bit, direction) & empty;
+                if target != 0 {
+                    moves.push(BitMove::quiet(from, target.trailing_zeros()));
+                }
+            }
+        }
+
+        moves
+    }
+
+    /// Count the legal moves for `player` without generating them, unless
+    /// they have to capture
+    pub fn count_moves(&self, player: Player) -> usize {
+        if self.jumpers(player) != 0 {
+            return self.legal_moves(player).len();
+        }
+
+        // Each peice has at most one quiet move in each direction
+        let empty = self.empty();
+        let men = self.peices(player) & !self.kings;
+        let kings = self.peices(player) & self.kings;
+        (0..4)
+            .map(|direction| {
+                let movers = if forward_directions(player).contains(&direction) {
+                    men | kings
+                } else {
+                    kings
+                };
+                (step(movers, direction) & empty).count_ones() as usize
+            })
+            .sum()
+    }
+
+    /// Follow every jump from the end of `path`, pushing completed chains
+    /// into `moves`
+    fn extend_jumps(
+        &self,
+        player: Player,
+        is_king: bool,
+        path: &mut [u8; MAX_PATH],
+        len: usize,
+        captured: u32,
+        moves: &mut Vec<BitMove>,
+    ) {
+        let current = 1 << path[len - 1];
+        // The moving peice has left its starting square
+        let empty = self.empty() | (1 << path[0]);
+        let enemies = self.peices(!player) & !captured;
+        let mut extended = false;
+
+        for direction in 0..4 {
+            if !is_king && !forward_directions(player).contains(&direction) {
+                continue;
+            }
+
+            let jumped = step(current, direction) & enemies;
+            let landing = step(jumped, direction) & empty;
+            if landing == 0 {
+                continue;
+            }
+
+            extended = true;
+            path[len] = landing.trailing_zeros() as u8;
+
+            if !is_king && landing & promotion_row(player) != 0 {
+                // Being crowned ends the move
+                moves.push(BitMove {
+                    path: *path,
+                    len: len as u8 + 1,
+                    captured: captured | jumped,
+                });
+            } else {
+                self.extend_jumps(player, is_king, path, len + 1, captured | jumped, moves);
+            }
+        }
+
+        if !extended && captured != 0 {
+            moves.push(BitMove {
+                path: *path,
+                len: len as u8,
+                captured,
+            });
+        }
+    }
+
+    /// Play a move which is known to be legal for `player`
+    pub fn apply(&mut self, player: Player, this_move: &BitMove) {
+        let from = 1 << this_move.from();
+        let to = 1 << this_move.to();
+        let is_king = self.kings & from != 0;
+
+        match player {
+            Player::Black => {
+                self.black ^= from | to;
+                self.white &= !this_move.captured;
+            }
+            Player::White => {
+                self.white ^= from | to;
+                self.black &= !this_move.captured;
+            }
+        }
+
+        self.kings &= !(this_move.captured | from);
+        if is_king || to & promotion_row(player) != 0 {
+            self.kings |= to;
+        }
+    }
+}
+
+impl fmt::Display for Bitboard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "B:{:08x} W:{:08x} K:{:08x}",
+            self.black, self.white, self.kings
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use crate::{board::Board, player::Player};
+
+    use super::{idx_to_square, square_to_idx, Bitboard};
+
+    #[test]
+    fn test_square_conversion() {
+        for square in 0..32 {
+            assert_eq!(idx_to_square(square_to_idx(square)), Some(square));
+        }
+        assert_eq!(square_to_idx(0), 0);
+        assert_eq!(square_to_idx(4), 9);
+        assert_eq!(idx_to_square(1), None);
+
+        let board = Board::new();
+        let bitboard = Bitboard::from_board(&board);
+        assert_eq!(bitboard.black, 0x0000_0FFF);
+        assert_eq!(bitboard.white, 0xFFF0_0000);
+
+        let mut copy = Board::empty();
+        bitboard.write_to(&mut copy);
+        assert_eq!(copy, board);
+    }
+
+    #[test]
+    fn test_matches_board_moves() {
+        let mut rng = StdRng::seed_from_u64(0x5eed);
+
+        for _ in 0..50 {
+            let mut board = Board::new();
+            let mut bitboard = Bitboard::from_board(&board);
+            let mut player = Player::Black;
+
+            for _ in 0..150 {
+                let mut expected = board.generic_legal_moves(player);
+                let bit_moves = bitboard.legal_moves(player);
+                assert_eq!(bitboard.count_moves(player), expected.len());
+                let mut actual: Vec<_> = bit_moves.iter().map(|m| m.as_move()).collect();
+                expected.sort();
+                actual.sort();
+                assert_eq!(actual, expected, "{board}");
+
+                if bit_moves.is_empty() {
+                    break;
+                }
+
+                let idx = rng.gen_range(0..bit_moves.len());
+                bitboard.apply(player, &bit_moves[idx]);
+                board
+                    .make_move_for(player, bit_moves[idx].as_move())
+                    .unwrap();
+                assert_eq!(Bitboard::from_board(&board), bitboard);
+
+                player = !player;
+            }
+        }
+    }
+}
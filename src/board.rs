@@ -1,6 +1,6 @@
 #![allow(clippy::needless_range_loop)]
 
-use std::cmp::{max, min};
+use std::cmp::Reverse;
 use std::fmt;
 use std::ops::{Index, IndexMut};
 
@@ -11,86 +11,186 @@ use tabled::{
     settings::{style::Style, themes::Colorization, Color},
 };
 
+use crate::bitboard::{idx_to_square, BitMove, Bitboard};
+use crate::eval::{self, EvalWeights};
+use crate::king_moves::king_moves;
 use crate::player::Player;
+use crate::rules::{CapturePriority, CapturePromotion, RuleSet, Variant};
 use crate::tile::{Tile, TileKind};
-use crate::{king_moves, r#move::*};
+use crate::zobrist;
+use crate::r#move::*;
 
+/// The size of an English draughts board, used by positions which are not
+/// given a board size
 pub const BOARD_SIZE: usize = 8;
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// The largest board supported, used for Canadian draughts
+pub const MAX_BOARD_SIZE: usize = 12;
+
+#[derive(Debug, Copy, Clone, Eq)]
 pub struct Board {
-    board: [Tile; BOARD_SIZE * BOARD_SIZE],
+    /// Tiles are stored row by row, only the first `size * size` are in use
+    board: [Tile; MAX_BOARD_SIZE * MAX_BOARD_SIZE],
+    size: usize,
+    variant: Variant,
+    /// The zobrist hash of the peices on the board, kept up to date as moves
+    /// are applied
+    hash: u64,
+    /// The peices as a bitboard, which moves are generated from when the
+    /// rules allow it. Like the hash this is kept up to date as moves are
+    /// applied.
+    bitboard: Option<Bitboard>,
+    /// Set when the tiles have been handed out mutably, so the hash and
+    /// bitboard may no longer match them
+    stale: bool,
 }
 
 impl Board {
     pub fn empty() -> Self {
+        Self::empty_with_variant(Variant::default())
+    }
+
+    pub fn empty_with_variant(variant: Variant) -> Self {
         Self {
-            board: [Tile::empty(); BOARD_SIZE * BOARD_SIZE],
+            board: [Tile::empty(); MAX_BOARD_SIZE * MAX_BOARD_SIZE],
+            size: variant.rules().board_size(),
+            variant,
+            hash: 0,
+            bitboard: Bitboard::supports(variant.rules()).then(Bitboard::default),
+            stale: false,
         }
     }
+
     pub fn new() -> Self {
-        let mut board = [Tile::empty(); BOARD_SIZE * BOARD_SIZE];
+        Self::with_variant(Variant::default())
+    }
 
-        for y in 0..3 {
-            for x in 0..BOARD_SIZE {
-                if (x + (y % 2)) % 2 == 0 {
-                    board[Board::coords_to_idx(x, y)].take_ownership(Player::Black);
+    /// Create a board set up for the start of a game played with the rules
+    /// of `variant`
+    pub fn with_variant(variant: Variant) -> Self {
+        let rules = variant.rules();
+        let rows = rules.rows_per_player();
+        let mut board = Self::empty_with_variant(variant);
+        let size = board.size;
+
+        for y in 0..size {
+            for x in 0..size {
+                if !rules.is_playable(x, y) {
+                    continue;
                 }
-            }
-        }
 
-        for y in (BOARD_SIZE - 3)..BOARD_SIZE {
-            for x in 0..BOARD_SIZE {
-                if (x + (y % 2)) % 2 == 0 {
-                    board[Board::coords_to_idx(x, y)].take_ownership(Player::White);
+                if y < rows {
+                    board.board[y * size + x].take_ownership(Player::Black);
+                } else if y >= size - rows {
+                    board.board[y * size + x].take_ownership(Player::White);
                 }
             }
         }
 
-        Self { board }
+        board.refresh();
+        board
+    }
+
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    /// The rules this board is being played with
+    pub fn rules(&self) -> &'static dyn RuleSet {
+        self.variant.rules()
+    }
+
+    /// Get the player who moves on a given turn
+    pub fn player_for_turn(&self, turn_id: usize) -> Player {
+        let first_player = self.rules().first_player();
+        if turn_id.is_multiple_of(2) {
+            first_player
+        } else {
+            !first_player
+        }
+    }
+
+    pub fn board(&self) -> &[Tile] {
+        &self.board[..self.size * self.size]
+    }
+
+    pub fn board_mut(&mut self) -> &mut [Tile] {
+        self.stale = true;
+        &mut self.board[..self.size * self.size]
+    }
+
+    /// The zobrist hash of the peices on the board. This doesn't include the
+    /// player to move, see `zobrist::side_key`.
+    pub fn hash(&self) -> u64 {
+        if self.stale {
+            self.compute_hash()
+        } else {
+            self.hash
+        }
+    }
+
+    fn compute_hash(&self) -> u64 {
+        self.board()
+            .iter()
+            .enumerate()
+            .fold(0, |hash, (idx, tile)| hash ^ zobrist::tile_key(idx, tile))
     }
 
-    pub fn board(&self) -> &[Tile; 64] {
-        &self.board
+    /// The peices as a bitboard, or `None` if the rules or the position can't
+    /// be played on one, see `Bitboard::try_from_board`
+    pub fn bitboard(&self) -> Option<Bitboard> {
+        if self.stale {
+            Bitboard::try_from_board(self)
+        } else {
+            self.bitboard
+        }
+    }
+
+    /// Recompute the hash and bitboard from the tiles
+    fn refresh(&mut self) {
+        self.hash = self.compute_hash();
+        self.bitboard = Bitboard::try_from_board(self);
+        self.stale = false;
+    }
+
+    /// The number of tiles along each side of the board
+    pub fn size(&self) -> usize {
+        self.size
     }
 
-    pub fn board_mut(&mut self) -> &mut [Tile; 64] {
-        &mut self.board
+    /// Get the position of a tile on this board from its index
+    pub fn position(&self, idx: usize) -> Position {
+        Position::from_idx_with_size(idx, self.size)
     }
 
-    /// Convert a pair of coordinates, `x`, and `y` to an index in the board array
+    /// Convert a pair of coordinates, `x`, and `y` to an index in the board
+    /// array of an 8x8 board
     pub fn coords_to_idx(x: usize, y: usize) -> usize {
         (y * BOARD_SIZE) + x
     }
 
-    /// Convert an index in the `boards` array to a pair of x, and y coordinates
+    /// Convert an index in the `boards` array of an 8x8 board to a pair of x,
+    /// and y coordinates
     pub fn idx_to_coords(idx: usize) -> (usize, usize) {
         (idx % BOARD_SIZE, idx / BOARD_SIZE)
     }
 
     /// Get the number of remaining peices for a given player
     pub fn get_remaining_peices(&self, player: Player) -> usize {
+        if let Some(bitboard) = self.bitboard() {
+            return bitboard.peices(player).count_ones() as usize;
+        }
+
         self.into_iter()
             .clone()
             .filter(|tile| tile.occupied_by == Some(player))
             .count()
     }
     
+    /// Score the board from the point of view of `moving_player` using the
+    /// default evaluation weights, see `eval::evaluate`
     pub(crate) fn evaluate_board(&self, moving_player: Player) -> isize {
-        let friendly_peices = self.get_idx_of_player_peices(moving_player);
-        let enemy_peices = self.get_idx_of_player_peices(!moving_player);
-        // let distance_to_promotion = todo!();
-        // Get number of peices where a capture is possible
-        let potential_captures = friendly_peices
-            .iter()
-            .filter(|idx| self.can_capture(moving_player, **idx))
-            .count();
-        let vulnerable_peices = enemy_peices
-            .iter()
-            .filter(|idx| self.can_capture(!moving_player, **idx))
-            .count();
-
-        potential_captures as isize - vulnerable_peices as isize
+        eval::evaluate(self, moving_player, &EvalWeights::default())
     }
 
     pub fn distance_to_position(source: Position, target: Position) -> f32 { 
@@ -100,123 +200,344 @@ impl Board {
         (((tx - sx).pow(2) + (ty - sy).pow(2)) as f32).sqrt()
     }
 
-    /// Handle moving the king
-    pub fn handle_king_movement(&mut self, moving_player: Player, this_move: Move) -> Result<()> {
-        if this_move.from() == this_move.to() {
-            return Err(anyhow!(
-                "Cannot move to the same position as where the piece started"
-            ));
+    /// Check if a player has won
+    pub fn has_player_won(&self, player: Player) -> bool {
+        self.get_remaining_peices(!player) == 0
+    }
+    
+    /// Get the indicies of all peices owned by a player 
+    pub fn get_idx_of_player_peices(&self, player: Player) -> Vec<Position> {
+        self.board()
+            .iter()
+            .enumerate()
+            .filter(|(_, tile)| tile.occupied_by != Some(!player) && !tile.is_empty())
+            .map(|(idx, _)| self.position(idx))
+            .collect()
+    }
+
+    /// Test if the player has a king 
+    pub fn has_king(&self, player: Player) -> bool {
+        if let Some(bitboard) = self.bitboard() {
+            return bitboard.peices(player) & bitboard.kings != 0;
         }
 
-        let delta = this_move.delta();
+        self.board()
+            .iter()
+            .any(|tile| tile.kind() == TileKind::King && tile.occupied_by == Some(player))
+    }
 
-        // Verify peice is moving diagonally
-        let (dx, dy) = delta;
+    pub fn can_capture(&self, player: Player, peice: Position) -> bool {
+        !self.get_captures_for_peice(player, peice).is_empty()
+    }
 
-        if dx.abs() != dy.abs() {
-            return Err(anyhow!("Peice must move diagonally"));
+    /// Get every legal move available to a player. Capturing is mandatory, so
+    /// if any of the players peices are able to capture, only capture chains
+    /// are returned.
+    ///
+    /// English boards generate their moves from the bitboard kept alongside
+    /// the tiles, which is several times faster than generating them tile by
+    /// tile, see `bench_legal_moves`.
+    pub fn get_legal_moves(&self, player: Player) -> Vec<Move> {
+        if let Some(bitboard) = self.bitboard() {
+            return bitboard
+                .legal_moves(player)
+                .iter()
+                .map(BitMove::as_move)
+                .collect();
         }
 
-        // Make sure the peice is able to move, i.e. is not blocked by any
-        // friendly peices, and capture any enemy peices in its way
-
-        let from_coords = this_move.from().coords();
-        let to_coords = this_move.to().coords();
+        self.generic_legal_moves(player)
+    }
 
-        let (sx, sy) = from_coords;
-        let (tx, ty) = to_coords;
+    /// Count the legal moves available to a player. English boards count them
+    /// without generating them unless a capture is available.
+    pub fn count_legal_moves(&self, player: Player) -> usize {
+        match self.bitboard() {
+            Some(bitboard) => bitboard.count_moves(player),
+            None => self.generic_legal_moves(player).len(),
+        }
+    }
 
-        // Generate a list of indicies the king must move over to get to the target
-        let moves: Vec<Position> = (min(sx, tx)..max(sx, tx))
-            .zip(min(sy, ty)..max(sy, ty))
-            .map(|(x, y)| Position::from_coords_checked(x as isize, y as isize))
-            .filter_map(|pos| pos.ok())
-            .collect();
+    /// Generate legal moves tile by tile, this works for every rule set but is
+    /// much slower than the bitboard move generator
+    pub(crate) fn generic_legal_moves(&self, player: Player) -> Vec<Move> {
+        let peices = self.get_idx_of_player_peices(player);
 
-        // Check the move is not blocked by any friendly peices, make sure to ignore the moving tile
-        let move_contains_friendly_peices = moves
+        let captures: Vec<Move> = peices
             .iter()
-            .any(|idx| self[*idx].occupied_by == Some(moving_player) && *idx != this_move.from());
+            .flat_map(|peice| self.get_captures_for_peice(player, *peice))
+            .collect();
 
-        if move_contains_friendly_peices {
-            return Err(anyhow!("Move blocked by friendly peice(s)"));
+        if !captures.is_empty() {
+            return self.prioritise_captures(captures);
         }
 
-        // Capture the peices in the way of the king
-        let captured_peices: Vec<Position> = moves
+        peices
             .iter()
-            .filter(|idx| self[**idx].occupied_by == Some(!moving_player))
-            .copied()
-            .collect();
+            .flat_map(|peice| self.get_quiet_moves_for_peice(player, *peice))
+            .collect()
+    }
 
-        for idx in captured_peices {
-            self[idx].leave()
-        }
+    /// Remove every capture which the rules do not allow the player to choose
+    fn prioritise_captures(&self, captures: Vec<Move>) -> Vec<Move> {
+        let rank = |m: &Move| match self.rules().capture_priority() {
+            CapturePriority::Free => (0, false, 0, Reverse(0)),
+            CapturePriority::MostPeices => (m.captured().len(), false, 0, Reverse(0)),
+            CapturePriority::Italian => {
+                let kings = m
+                    .captured()
+                    .iter()
+                    .filter(|pos| self[**pos].kind() == TileKind::King);
+                let first_king = m
+                    .captured()
+                    .iter()
+                    .position(|pos| self[*pos].kind() == TileKind::King)
+                    .unwrap_or(usize::MAX);
+                (
+                    m.captured().len(),
+                    self[m.from()].kind() == TileKind::King,
+                    kings.count(),
+                    Reverse(first_king),
+                )
+            }
+        };
 
-        self[this_move.from()].leave();
-        self[this_move.from()].demote();
-        self[this_move.to()].take_ownership(moving_player);
-        self[this_move.to()].promote();
+        let Some(best) = captures.iter().map(rank).max() else {
+            return captures;
+        };
 
-        Ok(())
+        captures.into_iter().filter(|m| rank(m) == best).collect()
     }
 
-    /// Check if a player has won
-    pub fn has_player_won(&self, player: Player) -> bool {
-        self.get_remaining_peices(!player) == 0
+    /// Find the legal move described by a list of positions separated by `-`
+    /// or `x`, such as `c3-d4` or `a3xc5xe7`, or the same in numeric notation
+    /// such as `11-15`. The intermediate landing tiles of a capture may be
+    /// left out if only one capture matches.
+    pub fn parse_move(&self, player: Player, text: &str) -> Result<Move> {
+        let path = text
+            .split(['-', 'x'])
+            .map(|pos| match pos.parse() {
+                Ok(square) => Position::from_square(square, self.variant),
+                Err(_) => Position::from_str_with_size(pos, self.size),
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        self.find_move(player, &path)
     }
-    
-    /// Get the indicies of all peices owned by a player 
-    pub fn get_idx_of_player_peices(&self, player: Player) -> Vec<Position> {
-        self.board
+
+    /// Find the legal move which lands on every tile in `path`, starting with
+    /// the tile the peice leaves. As with `parse_move`, a capture may be given
+    /// by just its first and last tiles if no other capture matches.
+    pub fn find_move(&self, player: Player, path: &[Position]) -> Result<Move> {
+        let text = path
             .iter()
-            .enumerate()
-            .filter(|(_, tile)| tile.occupied_by != Some(!player) && !tile.is_empty())
-            .map(|(idx, _)| Position::from_idx(idx))
-            .collect()
+            .map(|pos| pos.to_string())
+            .collect::<Vec<_>>()
+            .join("-");
+
+        if path.len() < 2 {
+            return Err(anyhow!("A move needs at least two positions, found {text}"));
+        }
+
+        let candidates: Vec<Move> = self
+            .get_legal_moves(player)
+            .into_iter()
+            .filter(|m| {
+                m.path() == path
+                    || (path.len() == 2 && m.from() == path[0] && m.to() == path[1])
+            })
+            .collect();
+
+        match candidates.len() {
+            0 => Err(anyhow!("{text} is not a legal move for {player}")),
+            1 => Ok(candidates[0].clone()),
+            _ => Err(anyhow!("{text} is ambiguous, list every tile the peice lands on")),
+        }
     }
 
-    /// Test if the player has a king 
-    pub fn has_king(&self, player: Player) -> bool {
-        self.board
+    /// Test if any of a players peices are able to capture
+    pub fn has_capture(&self, player: Player) -> bool {
+        if let Some(bitboard) = self.bitboard() {
+            return bitboard.jumpers(player) != 0;
+        }
+
+        self.get_idx_of_player_peices(player)
             .iter()
-            .any(|tile| tile.kind() == TileKind::King && tile.occupied_by == Some(player))
+            .any(|peice| self.can_capture(player, *peice))
     }
 
-    pub fn can_capture(&self, player: Player, peice: Position) -> bool {
-        if let TileKind::King = self[peice].kind() {
-            let locs = king_moves::KING_MOVES[peice.idx()];
+    /// Get every move a single peice can make, ignoring captures available to
+    /// the players other peices. If the peice is able to capture, only its
+    /// complete capture chains are returned.
+    pub fn get_moves_for_peice(&self, player: Player, peice: Position) -> Vec<Move> {
+        let captures = self.get_captures_for_peice(player, peice);
+        if !captures.is_empty() {
+            return captures;
+        }
 
-            let peices: Vec<_> = locs
-                .iter()
-                .map(|pos| self[Position::from_idx(*pos)])
-                .collect();
-            return peices.iter().any(|p| p.is_occupied_by(!player));
+        self.get_quiet_moves_for_peice(player, peice)
+    }
+
+    /// Get every non-capturing move a peice can make
+    pub fn get_quiet_moves_for_peice(&self, player: Player, peice: Position) -> Vec<Move> {
+        let tile = self[peice];
+        if !tile.is_occupied_by(player) {
+            return Vec::new();
         }
 
-        let (x, y) = peice.coords();
-        let y_offset: isize = if let Player::Black = player { 1 } else { -1 };
-        let locations_to_capture = (
-            Position::from_coords_checked((x as isize) - 1, (y as isize) + y_offset),
-            Position::from_coords_checked((x as isize) + 1, (y as isize) + y_offset),
-        );
-        let locations_to_move_to = (
-            Position::from_coords_checked((x as isize) - 2, (y as isize) + 2 * y_offset),
-            Position::from_coords_checked((x as isize) + 2, (y as isize) + 2 * y_offset),
+        let rays = &king_moves(self.size)[peice.idx()];
+        let mut moves = Vec::new();
+        for direction in self.move_directions(player, tile.kind(), false) {
+            for target in rays[direction].iter().map(|idx| self.position(*idx)) {
+                if !self[target].is_empty() {
+                    break;
+                }
+                moves.push(Move::from_positions(peice, target));
+
+                if tile.kind() == TileKind::Normal || !self.rules().flying_kings() {
+                    break;
+                }
+            }
+        }
+
+        moves
+    }
+
+    /// Get every complete capture chain a peice can make. A chain is only
+    /// complete once the peice has no further jumps available from the tile
+    /// it lands on.
+    pub fn get_captures_for_peice(&self, player: Player, peice: Position) -> Vec<Move> {
+        let tile = self[peice];
+        if !tile.is_occupied_by(player) {
+            return Vec::new();
+        }
+
+        let mut chains = Vec::new();
+        self.extend_capture_chain(
+            player,
+            tile.kind(),
+            &mut vec![peice],
+            &mut Vec::new(),
+            &mut chains,
         );
+        chains
+    }
 
-        match (locations_to_capture, locations_to_move_to) {
-            ((Ok(p1), Ok(p2)), _) => {
-                self[p1].occupied_by == Some(!player) || self[p1].occupied_by == Some(!player)
+    /// Recursively follow every jump available from the end of `path`,
+    /// pushing each chain that cannot be extended any further into `chains`.
+    /// Captured peices stay on the board until the move is over, so they
+    /// block the path and cannot be jumped a second time.
+    fn extend_capture_chain(
+        &self,
+        player: Player,
+        kind: TileKind,
+        path: &mut Vec<Position>,
+        captured: &mut Vec<Position>,
+        chains: &mut Vec<Move>,
+    ) {
+        let rules = self.rules();
+        let origin = path[0];
+        let current = path[path.len() - 1];
+        let is_free = |pos: Position| pos == origin || self[pos].is_empty();
+        let flying = kind == TileKind::King && rules.flying_kings();
+        let rays = &king_moves(self.size)[current.idx()];
+        let mut extended = false;
+
+        for direction in self.move_directions(player, kind, true) {
+            let ray: Vec<Position> = rays[direction].iter().map(|idx| self.position(*idx)).collect();
+
+            // Find the first peice along this diagonal which is within reach
+            let reach = if flying { ray.len() } else { 1 };
+            let Some(distance) = ray.iter().take(reach).position(|pos| !is_free(*pos)) else {
+                continue;
+            };
+            let jumped = ray[distance];
+
+            if !self[jumped].is_occupied_by(!player) || captured.contains(&jumped) {
+                continue;
+            }
+
+            if kind == TileKind::Normal
+                && self[jumped].kind() == TileKind::King
+                && !rules.men_capture_kings()
+            {
+                continue;
+            }
+
+            // Men land directly behind the captured peice, kings may land on
+            // any free tile behind it
+            for landing in ray.iter().skip(distance + 1).copied() {
+                if !is_free(landing) {
+                    break;
+                }
+
+                extended = true;
+                path.push(landing);
+                captured.push(jumped);
+
+                let promoted = kind == TileKind::Normal
+                    && landing.coords().1 == self.promotion_row(player);
+
+                match rules.capture_promotion() {
+                    CapturePromotion::EndsMove if promoted => {
+                        chains.push(Move::chain(path.clone(), captured.clone()));
+                    }
+                    CapturePromotion::ContinuesAsKing if promoted => {
+                        self.extend_capture_chain(player, TileKind::King, path, captured, chains);
+                    }
+                    _ => self.extend_capture_chain(player, kind, path, captured, chains),
+                }
+
+                path.pop();
+                captured.pop();
+
+                if !flying {
+                    break;
+                }
+            }
+        }
+
+        if !extended && !captured.is_empty() {
+            chains.push(Move::chain(path.clone(), captured.clone()));
+        }
+    }
+
+    /// The diagonals a peice is allowed to move along, as indicies into
+    /// `DIRECTIONS`. Men move towards the opponents side of the board, and may
+    /// capture backwards if the rules allow it. Kings may move in any
+    /// direction.
+    fn move_directions(&self, player: Player, kind: TileKind, capturing: bool) -> Vec<usize> {
+        let (forwards, backwards) = match player {
+            Player::Black => ([2, 3], [0, 1]),
+            Player::White => ([0, 1], [2, 3]),
+        };
+
+        match kind {
+            TileKind::Normal if capturing && self.rules().men_capture_backwards() => {
+                [forwards, backwards].concat()
             }
-            (_, _) => false,
+            TileKind::Normal => forwards.to_vec(),
+            TileKind::King => vec![0, 1, 2, 3],
         }
     }
 
-    /// Make a move
+    /// The row a players men are promoted on
+    pub(crate) fn promotion_row(&self, player: Player) -> usize {
+        match player {
+            Player::Black => self.size - 1,
+            Player::White => 0,
+        }
+    }
+
+    /// Make a move. Capture chains are executed in full, and are rejected if
+    /// they stop while the peice is still able to capture. Quiet moves are
+    /// rejected while any of the players peices has a capture available.
     pub fn make_move(&mut self, turn_id: usize, this_move: Move) -> Result<()> {
-        let delta = this_move.delta();
+        self.make_move_for(self.player_for_turn(turn_id), this_move)
+    }
 
+    /// Make a move as `moving_player`, regardless of whose turn it is
+    pub fn make_move_for(&mut self, moving_player: Player, this_move: Move) -> Result<()> {
         // Check no-ones already won
         if self.has_player_won(Player::Black) {
             return Err(anyhow!("Black has already won!"));
@@ -228,89 +549,184 @@ impl Board {
             return Err(anyhow!("Cannot move to the same position"));
         }
 
-        // check they're not trying to move a white piece
-        let moving_player = if turn_id % 2 == 0 {
-            Player::Black
-        } else {
-            Player::White
-        };
+        if this_move.path().iter().any(|pos| pos.size() != self.size) {
+            return Err(anyhow!("Move is for a different sized board"));
+        }
 
+        // check they're not trying to move the other players piece
         if !moving_player == self[this_move.from()].get_owner()? {
             return Err(anyhow!("Cannot move the other players piece!"));
         }
 
-        if self.can_capture(moving_player, this_move.from())
-            && !self[this_move.to()].is_occupied_by(!moving_player)
-        {
-            return Err(anyhow!("Capture available, try another move"));
+        let legal_moves: Vec<Move> = self
+            .get_legal_moves(moving_player)
+            .into_iter()
+            .filter(|m| m.from() == this_move.from())
+            .collect();
+
+        if let Some(legal_move) = legal_moves.iter().find(|m| m.path() == this_move.path()) {
+            self.apply_move(moving_player, legal_move);
+            return Ok(());
         }
 
-        // Check that normal tiles only move +1 tile diagonally forward
-        if let TileKind::Normal = self.board[this_move.from().idx()].kind() {
-            let (dx, dy) = delta;
+        let is_partial_chain = legal_moves
+            .iter()
+            .any(|m| m.path().starts_with(this_move.path()));
 
-            // Check that the peice is moving diagonally
-            if dx.abs() != dy.abs() {
-                return Err(anyhow!(
-                    "Normal peices cannot move more than one tile diagonally"
-                ));
-            }
+        if is_partial_chain {
+            Err(anyhow!("Capture incomplete, the peice must keep jumping"))
+        } else if self.has_capture(moving_player) && !this_move.is_capture() {
+            Err(anyhow!("Capture available, try another move"))
+        } else {
+            Err(anyhow!("Illegal move {this_move}"))
+        }
+    }
 
-            // Check that the peice is only moving one tile
-            if dx.abs() > 1 {
-                return Err(anyhow!("Normal peices can only move one tile diagonally"));
+    /// Move the peice, remove everything it captured and promote it if it
+    /// finished on the last row, or passed over it when the rules allow. The
+    /// move must already be known to be legal.
+    pub(crate) fn apply_move(&mut self, moving_player: Player, this_move: &Move) {
+        if self.stale {
+            self.refresh();
+        }
+
+        let moving_peice = self[this_move.from()];
+        let promotion_row = self.promotion_row(moving_player);
+        let promoted = match self.rules().capture_promotion() {
+            CapturePromotion::ContinuesAsKing => this_move.path()[1..]
+                .iter()
+                .any(|pos| pos.coords().1 == promotion_row),
+            _ => this_move.to().coords().1 == promotion_row,
+        };
+
+        // Tiles are changed directly so the hash and bitboard are updated
+        // incrementally rather than being marked as stale
+        let from = this_move.from().idx();
+        self.hash ^= zobrist::tile_key(from, &self.board[from]);
+        self.board[from].leave();
+        for captured in this_move.captured() {
+            let idx = captured.idx();
+            self.hash ^= zobrist::tile_key(idx, &self.board[idx]);
+            self.board[idx].leave();
+        }
+
+        let to = this_move.to().idx();
+        self.board[to] = moving_peice;
+        if promoted {
+            self.board[to].promote();
+        }
+        self.hash ^= zobrist::tile_key(to, &self.board[to]);
+
+        if let Some(bitboard) = &mut self.bitboard {
+            let captured = this_move.captured().iter().map(Position::idx);
+            for idx in captured.chain([from, to]) {
+                // SAFETY: the bitboard is only kept while every peice is on a
+                // playable tile, and a legal move only lands on playable tiles
+                bitboard.set(idx_to_square(idx).unwrap(), &self.board[idx]);
             }
+        }
+    }
 
-            // Check that the peice is moving forwards
-            let forwards = match moving_player {
-                Player::Black => -1,
-                Player::White => 1,
-            };
+    /// Read a position written in PDN-FEN, such as `W:W21,22,K30:B1,2,K9`.
+    /// The first letter is the player to move, followed by the squares of
+    /// each players peices in numeric notation, with kings marked by a `K`.
+    /// Runs of squares may be given as a range, such as `B1-12`.
+    pub fn from_fen(variant: Variant, fen: &str) -> Result<(Self, Player)> {
+        let parse_player = |text: &str| match text {
+            "W" | "w" => Ok(Player::White),
+            "B" | "b" => Ok(Player::Black),
+            _ => Err(anyhow!("Expected W or B in FEN, found {text}")),
+        };
 
-            if dy == forwards {
-                return Err(anyhow!("Normal peices cannot move backwards"));
+        let mut fields = fen.trim().trim_end_matches('.').split(':');
+        // SAFETY: split always returns at least one field
+        let player = parse_player(fields.next().unwrap().trim())?;
+
+        let mut board = Self::empty_with_variant(variant);
+        let mut seen = Vec::new();
+        for field in fields {
+            let field = field.trim();
+            if field.is_empty() {
+                continue;
             }
-        } else {
-            return self.handle_king_movement(moving_player, this_move);
-        }
-
-        // Check if target tile is occupied
-        if !self[this_move.to()].is_empty() {
-            if !moving_player == self[this_move.to()].get_owner()? {
-                // Handle taking an opponents peice by jumping over it
-                let to_coords = this_move.to().coords();
-                let next_tile = Position::from_coords_checked(
-                    to_coords.0 as isize + delta.0,
-                    to_coords.1 as isize + delta.1,
-                )?;
-
-                if self[next_tile].is_empty() {
-                    self[this_move.from()].leave();
-                    self[this_move.to()].leave();
-                    self[next_tile].take_ownership(moving_player);
-                    Ok(())
-                } else {
-                    Err(anyhow!("Next tile is already occupied!"))
+
+            let (letter, squares) = field.split_at(field.chars().next().map_or(0, char::len_utf8));
+            let owner = parse_player(letter)?;
+            if seen.contains(&owner) {
+                return Err(anyhow!("{owner}s peices are given twice in FEN"));
+            }
+            seen.push(owner);
+
+            for item in squares.split(',').map(str::trim).filter(|i| !i.is_empty()) {
+                let (king, squares) = match item.strip_prefix(['K', 'k']) {
+                    Some(squares) => (true, squares),
+                    None => (false, item),
+                };
+                let (first, last) = squares.split_once('-').unwrap_or((squares, squares));
+                let parse_square = |text: &str| {
+                    text.trim()
+                        .parse::<usize>()
+                        .map_err(|_| anyhow!("Invalid square {text} in FEN"))
+                };
+
+                for square in parse_square(first)?..=parse_square(last)? {
+                    let pos = Position::from_square(square, variant)?;
+                    let (_, y) = pos.coords();
+                    if !board[pos].is_empty() {
+                        return Err(anyhow!("Square {square} is given twice in FEN"));
+                    }
+                    if !king && y == board.promotion_row(owner) {
+                        return Err(anyhow!(
+                            "{owner} has a man on square {square}, which should have been crowned"
+                        ));
+                    }
+
+                    board[pos].take_ownership(owner);
+                    if king {
+                        board[pos].promote();
+                    }
                 }
-            } else {
-                Err(anyhow!("You already occupy this tile!"))
             }
-        } else {
-            // Simple move without capturing an enemy peice
-            self[this_move.from()].leave();
-            self[this_move.to()].take_ownership(moving_player);
-
-            // Check if the peice needs to be promoted
-            let last_row = match moving_player {
-                Player::Black => BOARD_SIZE - 1,
-                Player::White => 0,
-            };
+        }
 
-            if this_move.to().coords().1 == last_row {
-                self[this_move.to()].promote();
-            }
-            Ok(())
+        board.refresh();
+        Ok((board, player))
+    }
+
+    /// The position written in PDN-FEN, with `player` to move
+    pub fn fen(&self, player: Player) -> String {
+        let letter = |player| match player {
+            Player::White => "W",
+            Player::Black => "B",
+        };
+
+        let mut fen = letter(player).to_string();
+        for owner in [Player::White, Player::Black] {
+            let mut squares: Vec<(usize, bool)> = self
+                .board()
+                .iter()
+                .enumerate()
+                .filter(|(_, tile)| tile.is_occupied_by(owner))
+                .filter_map(|(idx, tile)| {
+                    let square = self.position(idx).square_number(self.variant)?;
+                    Some((square, tile.kind() == TileKind::King))
+                })
+                .collect();
+            squares.sort();
+
+            let squares: Vec<String> = squares
+                .into_iter()
+                .map(|(square, king)| if king { format!("K{square}") } else { square.to_string() })
+                .collect();
+            fen.push_str(&format!(":{}{}", letter(owner), squares.join(",")));
         }
+        fen
+    }
+}
+
+impl PartialEq for Board {
+    fn eq(&self, other: &Self) -> bool {
+        self.board == other.board && self.size == other.size && self.variant == other.variant
     }
 }
 
@@ -320,10 +736,10 @@ impl fmt::Display for Board {
         let color_black = Color::FG_WHITE | Color::BG_BLACK;
 
         let mut table = Builder::default();
-        for y in 0..BOARD_SIZE {
-            let mut row = vec![" ".to_string(); BOARD_SIZE];
-            for x in 0..BOARD_SIZE {
-                row[x] = format!("{}", self.board[Board::coords_to_idx(x, y)]);
+        for y in 0..self.size {
+            let mut row = vec![" ".to_string(); self.size];
+            for x in 0..self.size {
+                row[x] = format!("{}", self.board[y * self.size + x]);
             }
             table.push_record(row);
         }
@@ -334,14 +750,16 @@ impl fmt::Display for Board {
             .with(Colorization::chess(color_white, color_black));
         let table_str = format!("{}", table);
         let table_str: Vec<&str> = table_str.split('\n').collect();
-        write!(f, "  ")?;
-        for i in 0..BOARD_SIZE {
+        // Leave enough room for two digit row numbers on larger boards
+        let width = self.size.to_string().len();
+        write!(f, "{:width$} ", "")?;
+        for i in 0..self.size {
             write!(f, " {} ", (97 + i as u8) as char)?;
         }
         writeln!(f)?;
 
         for (idx, row) in table_str.iter().enumerate() {
-            writeln!(f, "{} {row}", idx + 1)?;
+            writeln!(f, "{:>width$} {row}", idx + 1)?;
         }
         write!(f, "")
     }
@@ -349,10 +767,10 @@ impl fmt::Display for Board {
 
 impl IntoIterator for Board {
     type Item = Tile;
-    type IntoIter = std::array::IntoIter<Self::Item, { BOARD_SIZE * BOARD_SIZE }>;
+    type IntoIter = std::iter::Take<std::array::IntoIter<Self::Item, { MAX_BOARD_SIZE * MAX_BOARD_SIZE }>>;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.board.into_iter()
+        self.board.into_iter().take(self.size * self.size)
     }
 }
 
@@ -366,6 +784,7 @@ impl Index<Position> for Board {
 
 impl IndexMut<Position> for Board {
     fn index_mut(&mut self, index: Position) -> &mut Self::Output {
+        self.stale = true;
         &mut self.board[index.idx()]
     }
 }
@@ -373,9 +792,11 @@ impl IndexMut<Position> for Board {
 #[cfg(test)]
 mod test {
     use crate::{
+        bitboard::Bitboard,
         board::{Tile, TileKind, BOARD_SIZE},
         player::Player,
-        r#move::{Move, Position},
+        r#move::{Move, Notation, Position},
+        rules::Variant,
     };
 
     use super::Board;
@@ -408,9 +829,9 @@ mod test {
     fn test_board_initializer() {
         let board = Board::new();
 
-        assert_eq!(board.board.len(), BOARD_SIZE * BOARD_SIZE);
+        assert_eq!(board.board().len(), BOARD_SIZE * BOARD_SIZE);
         assert_eq!(
-            board.board,
+            board.board(),
             [
                 tile!(Player::Black),
                 tile!(),
@@ -512,18 +933,19 @@ mod test {
         assert_eq!(board.board[43], tile!());
         assert_eq!(board.board[36], tile!(Player::White));
 
-        assert!(board.make_move(0, Move::new(29, 36)).is_ok());
+        // Captures are given as the tile the peice lands on
+        assert!(board.make_move(0, Move::new(29, 43)).is_ok());
 
         // Verify that capturing works as expected
         assert_eq!(board.board[29], tile!());
         assert_eq!(board.board[36], tile!());
 
         assert_eq! {
-           board.board[43],
+           board.board_mut()[43],
            tile!(Player::Black)
         };
 
-        assert!(board.make_move(1, Move::new(52, 43)).is_ok());
+        assert!(board.make_move(1, Move::new(52, 34)).is_ok());
 
         assert_eq!(board.board[43], tile!(),);
 
@@ -540,13 +962,13 @@ mod test {
 
         // Remove all white tiles from the board to make my life easier
         board
-            .board
+            .board_mut()
             .iter_mut()
             .filter(|tile| tile.is_empty() || tile.occupied_by == Some(Player::White))
             .for_each(|black_tile| black_tile.leave());
 
         // Add one white peice back so the game doesn't think black's won
-        board.board[63].take_ownership(Player::White);
+        board.board_mut()[63].take_ownership(Player::White);
 
         assert!(board.make_move(0, Move::new(22, 29)).is_ok());
         assert!(board.make_move(0, Move::new(29, 36)).is_ok());
@@ -560,37 +982,301 @@ mod test {
     fn test_king_movement() {
         let mut board = Board::new();
         // remove all peices from the board
-        board.board.iter_mut().for_each(|tile| tile.leave());
+        board.board_mut().iter_mut().for_each(|tile| tile.leave());
 
         assert_eq!(board.get_remaining_peices(Player::White), 0);
         assert_eq!(board.get_remaining_peices(Player::Black), 0);
 
-        board.board[42].take_ownership(Player::Black);
-        board.board[42].promote();
-        board.board[28].take_ownership(Player::White);
+        board.board_mut()[42].take_ownership(Player::Black);
+        board.board_mut()[42].promote();
+        board.board_mut()[28].take_ownership(Player::White);
 
         assert_eq!(board.board[42].kind(), TileKind::King);
         assert_eq!(board.board[28], tile!(Player::White));
         println!("{board}");
         println!("{}", Move::new(42, 14));
-        assert!(board.make_move(0, Move::new(42, 14)).is_ok());
+        // English kings move one square at a time, so can't reach a peice two
+        // squares away, see `test_flying_king_movement` for other variants
+        assert!(board.make_move(0, Move::new(42, 14)).is_err());
         assert!(board.make_move(0, Move::new(42, 42)).is_err());
 
-        assert_eq!(board.board[14], king!(Player::Black));
+        assert_eq!(board.board[42], king!(Player::Black));
+        assert_eq!(board.board[28], tile!(Player::White));
+
+        board.board_mut()[42].leave();
+        board.board_mut()[35] = king!(Player::Black);
+        board.board_mut()[17].take_ownership(Player::White);
+
+        assert!(board.make_move(0, Move::new(35, 17)).is_err());
+        assert_eq!(board.board[35], king!(Player::Black));
+
+        board.board_mut()[44].take_ownership(Player::White);
+        board.board_mut()[53].take_ownership(Player::White);
+
+        // The two peices next to each other can't be jumped, but the peice
+        // left on 28 is now next to the king, so must be captured
+        println!("{}", Move::new(35, 53));
+        assert!(board.make_move(0, Move::new(35, 53)).is_err());
+        assert!(board.make_move(0, Move::new(35, 42)).is_err());
+        assert!(board.make_move(0, Move::new(35, 21)).is_ok());
+        assert_eq!(board.board[21], king!(Player::Black));
         assert_eq!(board.board[28], tile!());
+    }
+
+    /// Kings in `variant` fly any distance along a diagonal, and can capture
+    /// a peice from a distance
+    fn check_flying_king_movement(variant: Variant) {
+        let size = variant.rules().board_size();
+        let pos = |x: usize, y: usize| Position::from_idx_with_size(y * size + x, size);
+        let mut board = Board::empty_with_variant(variant);
+
+        board[pos(2, 5)] = king!(Player::Black);
+        board[pos(4, 3)].take_ownership(Player::White);
+        let capture = Move::from_positions(pos(2, 5), pos(6, 1));
+        assert!(board.make_move_for(Player::Black, capture).is_ok());
+        assert_eq!(board[pos(6, 1)], king!(Player::Black));
+        assert_eq!(board[pos(4, 3)], tile!());
+
+        board[pos(6, 1)].leave();
+        board[pos(3, 4)] = king!(Player::Black);
+        board[pos(2, 3)].take_ownership(Player::White);
+        let capture = Move::from_positions(pos(3, 4), pos(1, 2));
+        assert!(board.make_move_for(Player::Black, capture).is_ok());
+        assert_eq!(board[pos(1, 2)], king!(Player::Black));
+        assert_eq!(board[pos(2, 3)], tile!());
+
+        // Two peices next to each other on a diagonal cannot be jumped
+        board[pos(4, 5)].take_ownership(Player::White);
+        board[pos(5, 6)].take_ownership(Player::White);
+        let blocked = Move::from_positions(pos(1, 2), pos(6, 7));
+        assert!(board.make_move_for(Player::Black, blocked).is_err());
+
+        // Without a capture to make the king can fly to any empty tile
+        let quiet = Move::from_positions(pos(1, 2), pos(3, 0));
+        assert!(board.make_move_for(Player::Black, quiet).is_ok());
+        assert_eq!(board[pos(3, 0)], king!(Player::Black));
+    }
+
+    #[test]
+    fn test_flying_king_movement_russian() {
+        check_flying_king_movement(Variant::Russian);
+    }
+
+    #[test]
+    fn test_flying_king_movement_brazilian() {
+        check_flying_king_movement(Variant::Brazilian);
+    }
+
+    #[test]
+    fn test_flying_king_movement_pool() {
+        check_flying_king_movement(Variant::Pool);
+    }
+
+    #[test]
+    fn test_flying_king_movement_international() {
+        check_flying_king_movement(Variant::International);
+    }
+
+    #[test]
+    fn test_flying_king_movement_canadian() {
+        check_flying_king_movement(Variant::Canadian);
+    }
+
+    #[test]
+    fn test_multi_jump() {
+        let mut board = Board::empty();
+
+        board.board_mut()[9].take_ownership(Player::Black);
+        board.board_mut()[18].take_ownership(Player::White);
+        board.board_mut()[36].take_ownership(Player::White);
+        board.board_mut()[54].take_ownership(Player::White);
+        board.board_mut()[15].take_ownership(Player::White);
+
+        let path = |idxs: &[usize]| idxs.iter().map(|idx| Position::from_idx(*idx)).collect();
+
+        // Stopping part way through the chain is not allowed
+        assert!(board.make_move(0, Move::new(9, 27)).is_err());
+        assert!(board
+            .make_move(0, Move::chain(path(&[9, 27, 45]), path(&[18, 36])))
+            .is_err());
 
-        board.board[14].leave();
-        board.board[35] = king!(Player::Black);
-        board.board[17].take_ownership(Player::White);
+        assert_eq!(
+            board.get_moves_for_peice(Player::Black, Position::from_idx(9)),
+            vec![Move::chain(path(&[9, 27, 45, 63]), path(&[18, 36, 54]))]
+        );
+        assert!(board
+            .make_move(0, Move::chain(path(&[9, 27, 45, 63]), Vec::new()))
+            .is_ok());
+
+        assert_eq!(board.board[63], king!(Player::Black));
+        assert_eq!(board.get_remaining_peices(Player::White), 1);
+        assert_eq!(board.board[9], tile!());
+    }
+
+    #[test]
+    fn test_mandatory_capture() {
+        let mut board = Board::empty();
 
-        assert!(board.make_move(0, Move::new(35, 17)).is_ok());
-        assert_eq!(board.board[17], king!(Player::Black));
+        board.board_mut()[Board::coords_to_idx(4, 3)].take_ownership(Player::Black);
+        board.board_mut()[Board::coords_to_idx(3, 4)].take_ownership(Player::White);
+        board.board_mut()[Board::coords_to_idx(0, 0)].take_ownership(Player::Black);
 
-        board.board[44].take_ownership(Player::White);
-        board.board[53].take_ownership(Player::White);
+        // The peice at a1 cannot capture, but the peice at e4 can
+        assert!(board.make_move(0, Move::new(0, 9)).is_err());
+        assert_eq!(
+            board.get_legal_moves(Player::Black),
+            vec![Move::chain(
+                vec![Position::from_coords(4, 3), Position::from_coords(2, 5)],
+                vec![Position::from_coords(3, 4)]
+            )]
+        );
 
-        println!("{}", Move::new(17, 62));
-        assert!(board.make_move(0, Move::new(17, 62)).is_ok());
+        board.board_mut()[Board::coords_to_idx(3, 4)].leave();
+        board.board_mut()[Board::coords_to_idx(7, 7)].take_ownership(Player::White);
+        assert!(!board.has_capture(Player::Black));
+        assert_eq!(board.get_legal_moves(Player::Black).len(), 3);
+        assert!(board.make_move(0, Move::new(0, 9)).is_ok());
+    }
+
+    #[test]
+    fn test_short_kings() {
+        let mut board = Board::empty();
+
+        board.board_mut()[42] = king!(Player::Black);
+        board.board_mut()[28].take_ownership(Player::White);
+
+        // English kings can only move one tile at a time
+        assert!(board.make_move(0, Move::new(42, 14)).is_err());
+        assert_eq!(
+            board.get_legal_moves(Player::Black),
+            vec![
+                Move::new(42, 33),
+                Move::new(42, 35),
+                Move::new(42, 49),
+                Move::new(42, 51)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_capture_promotion() {
+        let path = |idxs: &[usize]| -> Vec<Position> {
+            idxs.iter().map(|idx| Position::from_idx(*idx)).collect()
+        };
+        let setup = |variant: Variant| {
+            let mut board = Board::empty_with_variant(variant);
+            board.board_mut()[41].take_ownership(Player::Black);
+            board.board_mut()[50].take_ownership(Player::White);
+            board.board_mut()[52].take_ownership(Player::White);
+            board.board_mut()[15].take_ownership(Player::White);
+            board
+        };
+
+        // English men are crowned and stop
+        let board = setup(Variant::English);
+        assert_eq!(
+            board.get_legal_moves(Player::Black),
+            vec![Move::chain(path(&[41, 59]), path(&[50]))]
+        );
+
+        // Russian men are crowned and carry on capturing as a king
+        let mut board = setup(Variant::Russian);
+        assert_eq!(
+            board.get_legal_moves(Player::Black),
+            vec![
+                Move::chain(path(&[41, 59, 45]), path(&[50, 52])),
+                Move::chain(path(&[41, 59, 38]), path(&[50, 52])),
+                Move::chain(path(&[41, 59, 31]), path(&[50, 52])),
+            ]
+        );
+        assert!(board
+            .make_move(1, Move::chain(path(&[41, 59, 45]), Vec::new()))
+            .is_ok());
+        assert_eq!(board.board[45], king!(Player::Black));
+
+        // Brazilian men carry on capturing as a man, and are not crowned
+        let mut board = setup(Variant::Brazilian);
+        assert_eq!(
+            board.get_legal_moves(Player::Black),
+            vec![Move::chain(path(&[41, 59, 45]), path(&[50, 52]))]
+        );
+        assert!(board
+            .make_move(1, Move::chain(path(&[41, 59, 45]), Vec::new()))
+            .is_ok());
+        assert_eq!(board.board[45], tile!(Player::Black));
+    }
+
+    #[test]
+    fn test_capture_priority() {
+        let setup = |variant: Variant| {
+            let mut board = Board::empty_with_variant(variant);
+            board.board_mut()[18].take_ownership(Player::Black);
+            board.board_mut()[25].take_ownership(Player::White);
+            board.board_mut()[27].take_ownership(Player::White);
+            board.board_mut()[45].take_ownership(Player::White);
+            board
+        };
+
+        assert_eq!(setup(Variant::English).get_legal_moves(Player::Black).len(), 2);
+
+        let moves = setup(Variant::Brazilian).get_legal_moves(Player::Black);
+        assert_eq!(moves.len(), 1);
+        assert_eq!(moves[0].captured().len(), 2);
+
+        // Italian men may not capture kings
+        let mut board = Board::empty_with_variant(Variant::Italian);
+        board.board_mut()[17].take_ownership(Player::Black);
+        board.board_mut()[26] = king!(Player::White);
+        assert!(!board.can_capture(Player::Black, Position::from_idx(17)));
+        board.board_mut()[26] = tile!(Player::White);
+        assert!(board.can_capture(Player::Black, Position::from_idx(17)));
+    }
+
+    #[test]
+    fn test_variant_layout() {
+        let board = Board::with_variant(Variant::Italian);
+
+        assert_eq!(board.get_remaining_peices(Player::White), 12);
+        assert_eq!(board.get_remaining_peices(Player::Black), 12);
+        assert_eq!(board.board[0], tile!());
+        assert_eq!(board.board[1], tile!(Player::Black));
+        assert_eq!(board.player_for_turn(0), Player::White);
+        assert_eq!(Board::new().player_for_turn(0), Player::Black);
+    }
+
+    #[test]
+    fn test_international_board() {
+        let mut board = Board::with_variant(Variant::International);
+
+        assert_eq!(board.size(), 10);
+        assert_eq!(board.board().len(), 100);
+        assert_eq!(board.get_remaining_peices(Player::White), 20);
+        assert_eq!(board.get_remaining_peices(Player::Black), 20);
+        assert_eq!(board.get_legal_moves(Player::White).len(), 9);
+        assert!(format!("{board}").contains("10 "));
+
+        let pos = |s: &str| Position::from_str_with_size(s, 10).unwrap();
+        assert_eq!(pos("j10").coords(), (9, 9));
+        assert!(Position::from_str_with_size("k1", 10).is_err());
+
+        // A flying king must take the longest capture available
+        board.board_mut().iter_mut().for_each(|tile| tile.leave());
+        board[pos("a1")] = king!(Player::White);
+        board[pos("c3")].take_ownership(Player::Black);
+        board[pos("f6")].take_ownership(Player::Black);
+        board[pos("h6")].take_ownership(Player::Black);
+        board[pos("d2")].take_ownership(Player::Black);
+        board[pos("c1")] = king!(Player::White);
+
+        let moves = board.get_legal_moves(Player::White);
+        assert!(!moves.is_empty());
+        assert!(moves.iter().all(|m| m.captured().len() == 3));
+        assert!(board.make_move(0, moves[0].clone()).is_ok());
+        assert_eq!(board.get_remaining_peices(Player::Black), 1);
+
+        let board = Board::with_variant(Variant::Canadian);
+        assert_eq!(board.get_remaining_peices(Player::Black), 30);
+        assert_eq!(board.get_legal_moves(Player::White).len(), 11);
     }
 
     #[test]
@@ -615,4 +1301,214 @@ mod test {
         b.board_mut()[Board::coords_to_idx(7, 7)].take_ownership(Player::Black);
         assert!(!b.can_capture(Player::White, Position::from_coords(6, 6)));
     }
+
+    #[test]
+    fn test_numeric_notation() {
+        let pos = |s: &str| Position::from_str(s).unwrap();
+
+        // Black's men start on 1 to 12 and white's on 21 to 32
+        assert_eq!(pos("g1").square_number(Variant::English), Some(1));
+        assert_eq!(pos("a3").square_number(Variant::English), Some(12));
+        assert_eq!(pos("h6").square_number(Variant::English), Some(21));
+        assert_eq!(pos("b8").square_number(Variant::English), Some(32));
+        assert_eq!(pos("a2").square_number(Variant::English), None);
+
+        // The italian board is mirrored
+        assert_eq!(pos("h1").square_number(Variant::Italian), Some(1));
+        assert_eq!(pos("g1").square_number(Variant::Italian), None);
+
+        for variant in Variant::ALL {
+            let board = Board::empty_with_variant(variant);
+            let squares = board.size() * board.size() / 2;
+            for square in 1..=squares {
+                let pos = Position::from_square(square, variant).unwrap();
+                assert_eq!(pos.square_number(variant), Some(square));
+            }
+            assert!(Position::from_square(0, variant).is_err());
+            assert!(Position::from_square(squares + 1, variant).is_err());
+        }
+
+        // Moves can be read and written with numbers
+        let board = Board::new();
+        let quiet = board.parse_move(Player::Black, "11-15").unwrap();
+        assert_eq!(quiet, board.parse_move(Player::Black, "c3-d4").unwrap());
+        assert_eq!(
+            quiet.display(Notation::Numeric, Variant::English).to_string(),
+            "11-15"
+        );
+        assert_eq!(
+            quiet.display(Notation::Algebraic, Variant::English).to_string(),
+            "c3 -> d4"
+        );
+
+        let mut board = Board::empty();
+        board[pos("c3")].take_ownership(Player::Black);
+        board[pos("d4")].take_ownership(Player::White);
+        board[pos("f6")].take_ownership(Player::White);
+        let jump = board.parse_move(Player::Black, "11x25").unwrap();
+        assert_eq!(
+            jump.display(Notation::Numeric, Variant::English).to_string(),
+            "11x18x25"
+        );
+    }
+
+    #[test]
+    fn test_fen() {
+        let pos = |s: &str| Position::from_str(s).unwrap();
+
+        let (board, player) = Board::from_fen(Variant::English, "W:W21,22,K30:B1,2,K9").unwrap();
+        assert_eq!(player, Player::White);
+        assert_eq!(board[pos("h6")], tile!(Player::White));
+        assert_eq!(board[pos("f8")], king!(Player::White));
+        assert_eq!(board[pos("e1")], tile!(Player::Black));
+        assert_eq!(board[pos("g3")], king!(Player::Black));
+        assert_eq!(board.get_remaining_peices(Player::White), 3);
+        assert_eq!(board.hash(), {
+            let mut copy = board;
+            copy.board_mut();
+            copy.hash()
+        });
+        assert_eq!(board.fen(player), "W:W21,22,K30:B1,2,K9");
+
+        // The starting position, written with ranges
+        let (start, player) = Board::from_fen(Variant::English, "B:W21-32:B1-12.").unwrap();
+        assert_eq!(start, Board::new());
+        assert_eq!(player, Player::Black);
+        for variant in Variant::ALL {
+            let board = Board::with_variant(variant);
+            let fen = board.fen(Player::White);
+            assert_eq!(Board::from_fen(variant, &fen).unwrap(), (board, Player::White));
+        }
+
+        // Invalid positions are rejected
+        for fen in [
+            "X:W21:B1",
+            "W:W21:B1:W22",
+            "W:W21,21:B1",
+            "W:W33:B1",
+            "W:W1:B9",
+            "W:W21:B29",
+            "W:W21:Bx",
+        ] {
+            assert!(Board::from_fen(Variant::English, fen).is_err(), "{fen}");
+        }
+        assert!(Board::from_fen(Variant::English, "W:WK1:BK29").is_ok());
+    }
+
+    #[test]
+    fn test_zobrist_hash() {
+        for variant in [Variant::English, Variant::International] {
+            let mut board = Board::with_variant(variant);
+            let mut player = board.rules().first_player();
+            assert_eq!(board.hash(), board.compute_hash());
+            assert_ne!(board.hash(), Board::empty_with_variant(variant).hash());
+
+            // The incrementally updated hash always matches one computed
+            // from scratch, through captures and promotions
+            for turn in 0..80 {
+                let moves = board.get_legal_moves(player);
+                if moves.is_empty() {
+                    break;
+                }
+                board.apply_move(player, &moves[turn % moves.len()]);
+                assert_eq!(board.hash, board.compute_hash(), "{variant} {turn}");
+                player = !player;
+            }
+        }
+
+        // Reaching the same position through a different order of moves
+        // gives the same hash
+        let play = |moves: [&str; 3]| {
+            let mut board = Board::new();
+            for (text, player) in moves.iter().zip([Player::Black, Player::White, Player::Black]) {
+                let this_move = board.parse_move(player, text).unwrap();
+                board.make_move_for(player, this_move).unwrap();
+            }
+            board
+        };
+        let board = play(["c3-d4", "f6-g5", "a3-b4"]);
+        assert_eq!(board.hash(), play(["a3-b4", "f6-g5", "c3-d4"]).hash());
+        assert_ne!(board.hash(), play(["a3-b4", "f6-e5", "c3-d4"]).hash());
+
+        // Changing tiles directly is still reflected in the hash
+        let mut changed = board;
+        changed[Position::from_str("d4").unwrap()].promote();
+        assert_ne!(changed.hash(), board.hash());
+        assert_eq!(changed.hash(), changed.compute_hash());
+    }
+
+    #[test]
+    fn test_cached_bitboard() {
+        // The bitboard kept with the board always matches its tiles, through
+        // captures and promotions
+        let mut board = Board::new();
+        let mut player = Player::Black;
+        for turn in 0..120 {
+            let moves = board.get_legal_moves(player);
+            if moves.is_empty() {
+                break;
+            }
+            board.apply_move(player, &moves[(turn * 7) % moves.len()]);
+            assert_eq!(board.bitboard, Bitboard::try_from_board(&board), "{turn}\n{board}");
+            let expected = board.generic_legal_moves(!player).len();
+            assert_eq!(board.count_legal_moves(!player), expected);
+            player = !player;
+        }
+
+        // Changing tiles directly is still reflected in the bitboard
+        let mut changed = Board::new();
+        changed[Position::from_str("c3").unwrap()].leave();
+        assert_eq!(changed.get_remaining_peices(Player::Black), 11);
+        let this_move = changed.parse_move(Player::White, "f6-e5").unwrap();
+        changed.apply_move(Player::White, &this_move);
+        assert_eq!(changed.bitboard, Bitboard::try_from_board(&changed));
+
+        // Peices on tiles which can't be played on leave the bitboard unused,
+        // and variants it can't play never have one
+        changed[Position::from_str("a2").unwrap()].take_ownership(Player::White);
+        assert_eq!(changed.bitboard(), None);
+        assert_eq!(changed.get_remaining_peices(Player::White), 13);
+        assert_eq!(Board::with_variant(Variant::Russian).bitboard(), None);
+    }
+
+    /// Compare the bitboard move generator, including converting its moves
+    /// for the board, with the tile by tile one. Run
+    /// with `cargo test --release bench_legal_moves -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_legal_moves() {
+        use std::time::Instant;
+
+        fn walk(
+            board: &Board,
+            player: Player,
+            depth: usize,
+            generate: fn(&Board, Player) -> Vec<Move>,
+        ) -> u64 {
+            let moves = generate(board, player);
+            if depth == 0 {
+                return moves.len() as u64;
+            }
+            moves
+                .iter()
+                .map(|m| {
+                    let mut child = *board;
+                    child.apply_move(player, m);
+                    walk(&child, !player, depth - 1, generate)
+                })
+                .sum()
+        }
+
+        let board = Board::new();
+        let start = Instant::now();
+        let bitboard = walk(&board, Player::Black, 6, Board::get_legal_moves);
+        let bitboard_time = start.elapsed();
+        let start = Instant::now();
+        let generic = walk(&board, Player::Black, 6, Board::generic_legal_moves);
+        let generic_time = start.elapsed();
+
+        println!("bitboard: {bitboard_time:?}, tile by tile: {generic_time:?}");
+        assert_eq!(bitboard, generic);
+        assert!(bitboard_time < generic_time);
+    }
 }
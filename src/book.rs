@@ -0,0 +1,310 @@
+//! Opening books, which let the AI play well known openings straight away
+//! instead of searching. A book maps the hash of each position to the moves
+//! played from it, weighted by how often they were played, and the AI picks
+//! one of them at random in proportion to its weight.
+
+use std::{collections::HashMap, fmt, fs, path::Path, str::FromStr};
+
+use anyhow::{anyhow, bail, Context, Result};
+use rand::Rng;
+
+use crate::{board::Board, player::Player, r#move::Move, rules::Variant, zobrist};
+
+/// The number of plies from each game added to a book by default
+pub const DEFAULT_PLIES: usize = 16;
+
+/// A move in the book, written as its path such as `c3-d4` or `a3xc5xe7`
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct BookMove {
+    text: String,
+    weight: u32,
+}
+
+/// Books are stored as text, a `variant <name>` line followed by a line of
+/// `<position hash> <move> <weight>` for every move in the book
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpeningBook {
+    variant: Variant,
+    entries: HashMap<u64, Vec<BookMove>>,
+}
+
+/// The key of a position in the book, which includes the player to move
+fn key(board: &Board, player: Player) -> u64 {
+    board.hash() ^ zobrist::side_key(player)
+}
+
+impl OpeningBook {
+    pub fn new(variant: Variant) -> Self {
+        Self {
+            variant,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Build a book from game records, one game per line given as the moves
+    /// played from the start, e.g. `1. c3-d4 f6-e5 2. d4xf6 g7xe5`. Move
+    /// numbers and results are ignored, and only the first `plies` moves of
+    /// each game are added.
+    pub fn from_games(variant: Variant, games: &str, plies: usize) -> Result<Self> {
+        let mut book = Self::new(variant);
+        for (line_number, line) in games.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default();
+            let moves: Vec<&str> = line
+                .split_whitespace()
+                .filter(|text| !is_move_number(text) && !is_result(text))
+                .collect();
+            if moves.is_empty() {
+                continue;
+            }
+
+            book.add_game(&moves, plies)
+                .with_context(|| format!("Invalid game on line {}", line_number + 1))?;
+        }
+
+        Ok(book)
+    }
+
+    /// Replay a game from the start, adding each of its first `plies` moves
+    pub fn add_game(&mut self, moves: &[&str], plies: usize) -> Result<()> {
+        let mut board = Board::with_variant(self.variant);
+        let mut player = board.rules().first_player();
+
+        for text in moves.iter().take(plies) {
+            let this_move = board.parse_move(player, text)?;
+            self.add_move(&board, player, &this_move, 1);
+            board.apply_move(player, &this_move);
+            player = !player;
+        }
+
+        Ok(())
+    }
+
+    /// Add `weight` to a move from the given position
+    pub fn add_move(&mut self, board: &Board, player: Player, this_move: &Move, weight: u32) {
+        let text = this_move.notation();
+        let moves = self.entries.entry(key(board, player)).or_default();
+        match moves.iter_mut().find(|m| m.text == text) {
+            Some(book_move) => book_move.weight += weight,
+            None => moves.push(BookMove { text, weight }),
+        }
+    }
+
+    /// The number of positions in the book
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Every legal book move from a position, along with its weight
+    pub fn moves(&self, board: &Board, player: Player) -> Vec<(Move, u32)> {
+        if board.variant() != self.variant {
+            return Vec::new();
+        }
+
+        self.entries
+            .get(&key(board, player))
+            .into_iter()
+            .flatten()
+            .filter_map(|book_move| {
+                // A hash collision could give moves from another position, so
+                // only keep moves which are legal here
+                let this_move = board.parse_move(player, &book_move.text).ok()?;
+                Some((this_move, book_move.weight))
+            })
+            .collect()
+    }
+
+    /// Pick a book move at random, favouring the moves with more weight, or
+    /// `None` if the position isn't in the book
+    pub fn probe<R: Rng>(&self, board: &Board, player: Player, rng: &mut R) -> Option<Move> {
+        let moves = self.moves(board, player);
+        let total: u32 = moves.iter().map(|(_, weight)| weight).sum();
+        if total == 0 {
+            return None;
+        }
+
+        let mut choice = rng.gen_range(0..total);
+        for (this_move, weight) in moves {
+            if choice < weight {
+                return Some(this_move);
+            }
+            choice -= weight;
+        }
+
+        None
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        fs::read_to_string(path)
+            .with_context(|| format!("Failed to read opening book from {}", path.display()))?
+            .parse()
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        fs::write(path, self.to_string())
+            .with_context(|| format!("Failed to write opening book to {}", path.display()))
+    }
+}
+
+fn is_move_number(text: &str) -> bool {
+    text.strip_suffix('.')
+        .is_some_and(|number| number.chars().all(|c| c.is_ascii_digit()))
+}
+
+fn is_result(text: &str) -> bool {
+    matches!(text, "1-0" | "0-1" | "1/2-1/2" | "*")
+}
+
+impl FromStr for OpeningBook {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut book: Option<Self> = None;
+
+        for (line_number, line) in s.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(variant) = line.strip_prefix("variant ") {
+                if book.is_some() {
+                    bail!("Variant given twice on line {}", line_number + 1);
+                }
+                book = Some(Self::new(variant.parse()?));
+                continue;
+            }
+
+            let book = book.as_mut().ok_or_else(|| {
+                anyhow!("Expected `variant <name>` before line {}", line_number + 1)
+            })?;
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let [hash, text, weight] = fields[..] else {
+                bail!(
+                    "Expected `<hash> <move> <weight>` on line {}",
+                    line_number + 1
+                );
+            };
+
+            let hash = u64::from_str_radix(hash, 16)
+                .with_context(|| format!("Invalid hash on line {}", line_number + 1))?;
+            let weight = weight
+                .parse()
+                .with_context(|| format!("Invalid weight on line {}", line_number + 1))?;
+            book.entries.entry(hash).or_default().push(BookMove {
+                text: text.to_string(),
+                weight,
+            });
+        }
+
+        book.ok_or_else(|| anyhow!("Opening book is missing its variant"))
+    }
+}
+
+impl fmt::Display for OpeningBook {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "# Opening book, `<position hash> <move> <weight>`")?;
+        writeln!(f, "variant {}", self.variant)?;
+
+        // Sort the positions so the same book is always written the same way
+        let mut hashes: Vec<&u64> = self.entries.keys().collect();
+        hashes.sort();
+        for hash in hashes {
+            for book_move in &self.entries[hash] {
+                writeln!(f, "{hash:016x} {} {}", book_move.text, book_move.weight)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::env;
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::{board::Board, player::Player, rules::Variant};
+
+    use super::OpeningBook;
+
+    const GAMES: &str = "\
+# Three short games
+1. c3-d4 f6-e5 2. d4xf6 g7xe5 1-0
+1. c3-d4 f6-g5 *
+1. e3-f4 f6-e5
+";
+
+    #[test]
+    fn test_from_games() {
+        let book = OpeningBook::from_games(Variant::English, GAMES, 16).unwrap();
+        let mut board = Board::new();
+        let c3d4 = board.parse_move(Player::Black, "c3-d4").unwrap();
+        let e3f4 = board.parse_move(Player::Black, "e3-f4").unwrap();
+
+        let mut moves = book.moves(&board, Player::Black);
+        moves.sort();
+        assert_eq!(moves, vec![(c3d4.clone(), 2), (e3f4, 1)]);
+
+        // Every position along each game is in the book, captures included
+        board.apply_move(Player::Black, &c3d4);
+        assert_eq!(book.moves(&board, Player::White).len(), 2);
+        let f6e5 = board.parse_move(Player::White, "f6-e5").unwrap();
+        board.apply_move(Player::White, &f6e5);
+        let capture = board.parse_move(Player::Black, "d4xf6").unwrap();
+        assert_eq!(book.moves(&board, Player::Black), vec![(capture, 1)]);
+
+        // Only the first plies of each game are added
+        let short = OpeningBook::from_games(Variant::English, GAMES, 1).unwrap();
+        assert_eq!(short.len(), 1);
+
+        let error = OpeningBook::from_games(Variant::English, "c3-d4\nc3-d5", 16).unwrap_err();
+        assert_eq!(error.to_string(), "Invalid game on line 2");
+    }
+
+    #[test]
+    fn test_probe() {
+        let book = OpeningBook::from_games(Variant::English, GAMES, 16).unwrap();
+        let board = Board::new();
+        let mut rng = StdRng::seed_from_u64(7);
+
+        // Both opening moves are played, the more common one more often
+        let mut c3d4 = 0;
+        for _ in 0..300 {
+            let this_move = book.probe(&board, Player::Black, &mut rng).unwrap();
+            if this_move.to_string() == "c3 -> d4" {
+                c3d4 += 1;
+            }
+        }
+        assert!((150..300).contains(&c3d4));
+
+        // Positions out of the book, or from another variant, have no moves
+        assert_eq!(book.probe(&board, Player::White, &mut rng), None);
+        let russian = Board::with_variant(Variant::Russian);
+        assert_eq!(book.probe(&russian, Player::White, &mut rng), None);
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let book = OpeningBook::from_games(Variant::English, GAMES, 16).unwrap();
+        let path = env::temp_dir().join(format!("checkers-book-{}", std::process::id()));
+
+        book.save(&path).unwrap();
+        assert_eq!(OpeningBook::load(&path).unwrap(), book);
+        std::fs::remove_file(&path).unwrap();
+
+        assert!("0123 c3-d4 1".parse::<OpeningBook>().is_err());
+        assert!("variant Chess".parse::<OpeningBook>().is_err());
+        assert!("variant english\n0123 c3-d4"
+            .parse::<OpeningBook>()
+            .is_err());
+        assert!("variant english\nxyz c3-d4 1"
+            .parse::<OpeningBook>()
+            .is_err());
+    }
+}
@@ -0,0 +1,184 @@
+//! Clocks for timed games. Each player has their own time, which only runs
+//! down while it is their move, and may be given a little more after every
+//! move they make.
+
+use std::{fmt, str::FromStr, time::Duration};
+
+use anyhow::{anyhow, Context, Result};
+
+use crate::{player::Player, search::TimeControl};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Clock {
+    black: Duration,
+    white: Duration,
+    /// The time added to a players clock after each of their moves
+    increment: Duration,
+}
+
+impl Clock {
+    /// Give both players `time` for the game
+    pub fn new(time: Duration, increment: Duration) -> Self {
+        Self {
+            black: time,
+            white: time,
+            increment,
+        }
+    }
+
+    /// Set the time each player has left, e.g. when resuming a game
+    pub fn with_remaining(black: Duration, white: Duration, increment: Duration) -> Self {
+        Self {
+            black,
+            white,
+            increment,
+        }
+    }
+
+    pub fn remaining(&self, player: Player) -> Duration {
+        match player {
+            Player::Black => self.black,
+            Player::White => self.white,
+        }
+    }
+
+    pub fn increment(&self) -> Duration {
+        self.increment
+    }
+
+    fn remaining_mut(&mut self, player: Player) -> &mut Duration {
+        match player {
+            Player::Black => &mut self.black,
+            Player::White => &mut self.white,
+        }
+    }
+
+    /// Take the time `player` spent thinking off their clock
+    pub fn spend(&mut self, player: Player, elapsed: Duration) {
+        let remaining = self.remaining_mut(player);
+        *remaining = remaining.saturating_sub(elapsed);
+    }
+
+    /// Add the increment once `player` has moved, as long as they didn't run
+    /// out of time first
+    pub fn finish_move(&mut self, player: Player) {
+        let increment = self.increment;
+        if !self.is_flagged(player) {
+            *self.remaining_mut(player) += increment;
+        }
+    }
+
+    /// Whether `player` has run out of time
+    pub fn is_flagged(&self, player: Player) -> bool {
+        self.remaining(player).is_zero()
+    }
+
+    /// How an AI playing as `player` should share out its time
+    pub fn time_control(&self, player: Player) -> TimeControl {
+        TimeControl::Clock {
+            remaining: self.remaining(player),
+            increment: self.increment,
+            moves_to_go: None,
+        }
+    }
+}
+
+/// Write a duration as minutes and seconds, e.g. `4:05`
+pub fn format_time(time: Duration) -> String {
+    let seconds = time.as_secs();
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+impl fmt::Display for Clock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Black {}, White {}",
+            format_time(self.black),
+            format_time(self.white)
+        )
+    }
+}
+
+impl FromStr for Clock {
+    type Err = anyhow::Error;
+
+    /// Read a time control given as `<minutes>[+<increment seconds>]`, e.g.
+    /// `5` or `3+2`
+    fn from_str(s: &str) -> Result<Self> {
+        let (minutes, increment) = match s.split_once('+') {
+            Some((minutes, increment)) => (minutes, Some(increment)),
+            None => (s, None),
+        };
+
+        let minutes: f64 = minutes
+            .trim()
+            .parse()
+            .with_context(|| format!("Invalid number of minutes in {s}"))?;
+        let increment: f64 = match increment {
+            Some(increment) => increment
+                .trim()
+                .parse()
+                .with_context(|| format!("Invalid increment in {s}"))?,
+            None => 0.0,
+        };
+        let invalid = || anyhow!("Invalid time control {s}");
+        if minutes <= 0.0 {
+            return Err(invalid());
+        }
+
+        // Negative times, and times too long to count, don't fit in a duration
+        Ok(Self::new(
+            Duration::try_from_secs_f64(minutes * 60.0).map_err(|_| invalid())?,
+            Duration::try_from_secs_f64(increment).map_err(|_| invalid())?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::{player::Player, search::TimeControl};
+
+    use super::Clock;
+
+    #[test]
+    fn test_clock() {
+        let mut clock: Clock = "3+2".parse().unwrap();
+        assert_eq!(clock.remaining(Player::Black), Duration::from_secs(180));
+        assert_eq!(clock.increment(), Duration::from_secs(2));
+
+        // Only the moving players time runs down
+        clock.spend(Player::Black, Duration::from_secs(10));
+        clock.finish_move(Player::Black);
+        assert_eq!(clock.remaining(Player::Black), Duration::from_secs(172));
+        assert_eq!(clock.remaining(Player::White), Duration::from_secs(180));
+        assert_eq!(clock.to_string(), "Black 2:52, White 3:00");
+        assert!(matches!(
+            clock.time_control(Player::White),
+            TimeControl::Clock { remaining, .. } if remaining == Duration::from_secs(180)
+        ));
+
+        // Running out of time loses the increment too
+        clock.spend(Player::White, Duration::from_secs(200));
+        clock.finish_move(Player::White);
+        assert!(clock.is_flagged(Player::White));
+        assert!(!clock.is_flagged(Player::Black));
+
+        assert_eq!(
+            "0.5".parse::<Clock>().unwrap().remaining(Player::White),
+            Duration::from_secs(30)
+        );
+        assert!("".parse::<Clock>().is_err());
+        assert!("0".parse::<Clock>().is_err());
+        assert!("5+x".parse::<Clock>().is_err());
+        assert!("5+-1".parse::<Clock>().is_err());
+
+        // Times too long to fit in a duration are an error rather than a crash
+        assert!("1e300".parse::<Clock>().is_err());
+        assert!("5+1e300".parse::<Clock>().is_err());
+        assert!("inf".parse::<Clock>().is_err());
+        assert!("NaN".parse::<Clock>().is_err());
+    }
+}
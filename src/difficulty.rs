@@ -0,0 +1,431 @@
+//! How strong the AI plays, and what style it plays in.
+
+use std::{fmt, str::FromStr, time::Duration};
+
+use anyhow::{anyhow, Result};
+use dialoguer::Select;
+
+use crate::{
+    eval::EvalWeights,
+    mcts::{MctsConfig, MctsLimit},
+    search::{SearchConfig, TimeControl, MAX_DEPTH},
+};
+
+/// Find the option whose name or key matches `name`, ignoring case
+fn from_name<T: Copy + fmt::Display>(
+    options: &[T],
+    key: fn(&T) -> &'static str,
+    name: &str,
+    kind: &str,
+) -> Result<T> {
+    let name = name.trim();
+    options
+        .iter()
+        .copied()
+        .find(|option| {
+            option.to_string().eq_ignore_ascii_case(name) || key(option).eq_ignore_ascii_case(name)
+        })
+        .ok_or_else(|| anyhow!("Unknown {kind} {name}"))
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Difficulty {
+    Beginner,
+    Easy,
+    Medium,
+    #[default]
+    Hard,
+    Expert,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 5] = [
+        Difficulty::Beginner,
+        Difficulty::Easy,
+        Difficulty::Medium,
+        Difficulty::Hard,
+        Difficulty::Expert,
+    ];
+
+    /// A short name which never changes, used in save files
+    pub fn key(&self) -> &'static str {
+        match self {
+            Difficulty::Beginner => "beginner",
+            Difficulty::Easy => "easy",
+            Difficulty::Medium => "medium",
+            Difficulty::Hard => "hard",
+            Difficulty::Expert => "expert",
+        }
+    }
+
+    /// The deepest the AI will look ahead
+    pub fn depth(&self) -> usize {
+        match self {
+            Difficulty::Beginner => 2,
+            Difficulty::Easy => 4,
+            Difficulty::Medium => 8,
+            Difficulty::Hard | Difficulty::Expert => MAX_DEPTH,
+        }
+    }
+
+    /// The longest the AI will think about each move
+    pub fn time(&self) -> Duration {
+        match self {
+            Difficulty::Beginner => Duration::from_millis(200),
+            Difficulty::Easy => Duration::from_millis(300),
+            Difficulty::Medium => Duration::from_millis(500),
+            Difficulty::Hard => Duration::from_secs(1),
+            Difficulty::Expert => Duration::from_secs(3),
+        }
+    }
+
+    /// The most a random amount added to each evaluation can be, in
+    /// hundredths of a man. This blurs the AIs judgement of close positions.
+    pub fn eval_noise(&self) -> isize {
+        match self {
+            Difficulty::Beginner => 60,
+            Difficulty::Easy => 30,
+            Difficulty::Medium => 10,
+            Difficulty::Hard | Difficulty::Expert => 0,
+        }
+    }
+
+    /// The chance of the AI ignoring its search and playing a random move
+    pub fn blunder_rate(&self) -> f64 {
+        match self {
+            Difficulty::Beginner => 0.25,
+            Difficulty::Easy => 0.1,
+            Difficulty::Medium => 0.03,
+            Difficulty::Hard | Difficulty::Expert => 0.0,
+        }
+    }
+
+    pub fn select_difficulty(prompt: &str) -> Result<Self> {
+        let selection = Select::new()
+            .with_prompt(prompt)
+            .items(&Self::ALL)
+            .default(Self::default() as usize)
+            .interact()?;
+
+        Ok(Self::ALL[selection])
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Difficulty::Beginner => "Beginner",
+            Difficulty::Easy => "Easy",
+            Difficulty::Medium => "Medium",
+            Difficulty::Hard => "Hard",
+            Difficulty::Expert => "Expert",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        from_name(&Self::ALL, Self::key, s, "difficulty")
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Personality {
+    #[default]
+    Balanced,
+    /// Pushes its men forward and fights for the centre, at the cost of
+    /// leaving its back row open
+    Aggressive,
+    /// Keeps its back row guarded and avoids weakening its position
+    Defensive,
+    /// Values kings highly, so races to crown its men and goes after the
+    /// opponents kings
+    KingHunting,
+}
+
+impl Personality {
+    pub const ALL: [Personality; 4] = [
+        Personality::Balanced,
+        Personality::Aggressive,
+        Personality::Defensive,
+        Personality::KingHunting,
+    ];
+
+    /// A short name which never changes, used in save files
+    pub fn key(&self) -> &'static str {
+        match self {
+            Personality::Balanced => "balanced",
+            Personality::Aggressive => "aggressive",
+            Personality::Defensive => "defensive",
+            Personality::KingHunting => "king-hunting",
+        }
+    }
+
+    /// Adjust `weights` to suit this style of play
+    pub fn apply(&self, weights: EvalWeights) -> EvalWeights {
+        match self {
+            Personality::Balanced => weights,
+            Personality::Aggressive => EvalWeights {
+                advancement: weights.advancement * 2,
+                centre: weights.centre * 2,
+                mobility: weights.mobility * 2,
+                back_rank: weights.back_rank / 2,
+                ..weights
+            },
+            Personality::Defensive => EvalWeights {
+                back_rank: weights.back_rank * 3,
+                advancement: weights.advancement / 2,
+                mobility: weights.mobility / 2,
+                ..weights
+            },
+            Personality::KingHunting => EvalWeights {
+                king: weights.king + weights.man / 2,
+                runaway: weights.runaway * 2,
+                advancement: weights.advancement * 2,
+                ..weights
+            },
+        }
+    }
+
+    pub fn select_personality(prompt: &str) -> Result<Self> {
+        let selection = Select::new()
+            .with_prompt(prompt)
+            .items(&Self::ALL)
+            .default(0)
+            .interact()?;
+
+        Ok(Self::ALL[selection])
+    }
+}
+
+impl fmt::Display for Personality {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Personality::Balanced => "Balanced",
+            Personality::Aggressive => "Aggressive",
+            Personality::Defensive => "Defensive",
+            Personality::KingHunting => "King hunting",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl FromStr for Personality {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        from_name(&Self::ALL, Self::key, s, "personality")
+    }
+}
+
+/// The search algorithm an AI uses to choose its moves
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Engine {
+    #[default]
+    AlphaBeta,
+    MonteCarlo,
+}
+
+impl Engine {
+    pub const ALL: [Engine; 2] = [Engine::AlphaBeta, Engine::MonteCarlo];
+
+    /// A short name which never changes, used in save files
+    pub fn key(&self) -> &'static str {
+        match self {
+            Engine::AlphaBeta => "alpha-beta",
+            Engine::MonteCarlo => "mcts",
+        }
+    }
+
+    pub fn select_engine(prompt: &str) -> Result<Self> {
+        let selection = Select::new()
+            .with_prompt(prompt)
+            .items(&Self::ALL)
+            .default(0)
+            .interact()?;
+
+        Ok(Self::ALL[selection])
+    }
+}
+
+impl fmt::Display for Engine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Engine::AlphaBeta => "Alpha-beta search",
+            Engine::MonteCarlo => "Monte Carlo tree search",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl FromStr for Engine {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        from_name(&Self::ALL, Self::key, s, "engine")
+    }
+}
+
+/// Everything needed to set up an AI player
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct AiSettings {
+    pub engine: Engine,
+    pub difficulty: Difficulty,
+    pub personality: Personality,
+}
+
+impl AiSettings {
+    /// Ask for the difficulty and personality of an AI, `name` is used to
+    /// tell apart the AIs when there is more than one
+    pub fn select(name: &str) -> Result<Self> {
+        Ok(Self {
+            engine: Engine::select_engine(&format!("Select the {name} engine"))?,
+            difficulty: Difficulty::select_difficulty(&format!("Select the {name} difficulty"))?,
+            personality: Personality::select_personality(&format!(
+                "Select the {name} personality"
+            ))?,
+        })
+    }
+
+    /// The settings written with the key of each part, which can be read
+    /// back by `from_str` whatever the parts are called on screen
+    pub fn key(&self) -> String {
+        format!(
+            "{}, {}, {}",
+            self.engine.key(),
+            self.difficulty.key(),
+            self.personality.key()
+        )
+    }
+
+    /// Apply these settings on top of the `base` search settings. A clock in
+    /// `base` is kept, so in timed games the AI shares out its own time.
+    pub fn search_config(&self, base: SearchConfig) -> SearchConfig {
+        let time = match base.time {
+            TimeControl::Clock { .. } => base.time,
+            _ => TimeControl::PerMove(self.difficulty.time()),
+        };
+        SearchConfig {
+            depth: self.difficulty.depth(),
+            time,
+            eval_noise: self.difficulty.eval_noise(),
+            weights: self.personality.apply(base.weights),
+            ..base
+        }
+    }
+
+    /// Apply these settings on top of the `base` Monte Carlo settings. The
+    /// search depth and evaluation noise have no equivalent, so only the
+    /// time and weights are used.
+    pub fn mcts_config(&self, base: MctsConfig) -> MctsConfig {
+        MctsConfig {
+            limit: MctsLimit::Time(self.difficulty.time()),
+            weights: self.personality.apply(base.weights),
+            ..base
+        }
+    }
+}
+
+impl fmt::Display for AiSettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}, {}, {}", self.engine, self.difficulty, self.personality)
+    }
+}
+
+impl FromStr for AiSettings {
+    type Err = anyhow::Error;
+
+    /// Read settings written as `engine, difficulty, personality`
+    fn from_str(s: &str) -> Result<Self> {
+        let fields: Vec<&str> = s.split(',').collect();
+        let [engine, difficulty, personality] = fields[..] else {
+            return Err(anyhow!(
+                "Expected AI settings as `engine, difficulty, personality`, found {s}"
+            ));
+        };
+
+        Ok(Self {
+            engine: engine.parse()?,
+            difficulty: difficulty.parse()?,
+            personality: personality.parse()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::{
+        eval::EvalWeights,
+        search::{SearchConfig, TimeControl},
+    };
+
+    use super::{AiSettings, Difficulty, Engine, Personality};
+
+    #[test]
+    fn test_settings() {
+        // The default settings play like the AI always has
+        let config = AiSettings::default().search_config(SearchConfig::default());
+        assert_eq!(config, SearchConfig::default());
+
+        // Harder levels search deeper and make fewer mistakes
+        for pair in Difficulty::ALL.windows(2) {
+            let (easier, harder) = (pair[0], pair[1]);
+            assert!(easier.depth() <= harder.depth());
+            assert!(easier.time() <= harder.time());
+            assert!(easier.eval_noise() >= harder.eval_noise());
+            assert!(easier.blunder_rate() >= harder.blunder_rate());
+        }
+
+        let weights = EvalWeights::default();
+        let aggressive = Personality::Aggressive.apply(weights);
+        let defensive = Personality::Defensive.apply(weights);
+        let king_hunting = Personality::KingHunting.apply(weights);
+        assert!(aggressive.advancement > defensive.advancement);
+        assert!(defensive.back_rank > aggressive.back_rank);
+        assert!(king_hunting.king > weights.king);
+        assert_eq!(king_hunting.man, weights.man);
+
+        // A clock is left for the search to share out
+        let clock = TimeControl::Clock {
+            remaining: Duration::from_secs(60),
+            increment: Duration::ZERO,
+            moves_to_go: None,
+        };
+        let base = SearchConfig {
+            time: clock,
+            ..SearchConfig::default()
+        };
+        assert_eq!(AiSettings::default().search_config(base).time, clock);
+    }
+
+    #[test]
+    fn test_settings_text() {
+        let settings = AiSettings {
+            engine: Engine::MonteCarlo,
+            difficulty: Difficulty::Beginner,
+            personality: Personality::KingHunting,
+        };
+        assert_eq!(
+            settings.to_string(),
+            "Monte Carlo tree search, Beginner, King hunting"
+        );
+        assert_eq!(settings.to_string().parse::<AiSettings>().unwrap(), settings);
+        assert_eq!(
+            " alpha-beta search,hard , balanced".parse::<AiSettings>().unwrap(),
+            AiSettings::default()
+        );
+
+        assert_eq!(settings.key(), "mcts, beginner, king-hunting");
+        assert_eq!(settings.key().parse::<AiSettings>().unwrap(), settings);
+
+        assert!("Alpha-beta search, Hard".parse::<AiSettings>().is_err());
+        assert!("Alpha-beta search, Impossible, Balanced"
+            .parse::<AiSettings>()
+            .is_err());
+    }
+}
@@ -0,0 +1,279 @@
+//! Static evaluation of positions, used to score the leaves of the search.
+
+use std::{fmt, fs, path::Path, str::FromStr};
+
+use anyhow::{anyhow, Context, Result};
+
+use crate::{bitboard::square_to_idx, board::Board, player::Player, tile::TileKind};
+
+/// How much each feature of a position is worth, in hundredths of a man.
+/// Weights can be written to and read from a file of `name = value` lines.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct EvalWeights {
+    pub man: isize,
+    pub king: isize,
+    /// Per row each man has advanced from its own back row
+    pub advancement: isize,
+    /// Per man still guarding its own back row, stopping the opponent from
+    /// promoting there
+    pub back_rank: isize,
+    /// Per peice in the middle of the board
+    pub centre: isize,
+    /// Per legal move available
+    pub mobility: isize,
+    /// Per man which can't be stopped from reaching the king row
+    pub runaway: isize,
+    /// Bonus for being the player to move
+    pub tempo: isize,
+}
+
+impl Default for EvalWeights {
+    fn default() -> Self {
+        Self {
+            man: 100,
+            king: 130,
+            advancement: 3,
+            back_rank: 8,
+            centre: 4,
+            mobility: 2,
+            runaway: 40,
+            tempo: 5,
+        }
+    }
+}
+
+impl EvalWeights {
+    /// Read weights from a file. Weights missing from the file keep their
+    /// default values.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        fs::read_to_string(path)
+            .with_context(|| format!("Failed to read weights from {}", path.display()))?
+            .parse()
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        fs::write(path, self.to_string())
+            .with_context(|| format!("Failed to write weights to {}", path.display()))
+    }
+
+    fn fields(&self) -> [(&'static str, isize); 8] {
+        [
+            ("man", self.man),
+            ("king", self.king),
+            ("advancement", self.advancement),
+            ("back_rank", self.back_rank),
+            ("centre", self.centre),
+            ("mobility", self.mobility),
+            ("runaway", self.runaway),
+            ("tempo", self.tempo),
+        ]
+    }
+
+    fn field_mut(&mut self, name: &str) -> Option<&mut isize> {
+        Some(match name {
+            "man" => &mut self.man,
+            "king" => &mut self.king,
+            "advancement" => &mut self.advancement,
+            "back_rank" => &mut self.back_rank,
+            "centre" => &mut self.centre,
+            "mobility" => &mut self.mobility,
+            "runaway" => &mut self.runaway,
+            "tempo" => &mut self.tempo,
+            _ => return None,
+        })
+    }
+}
+
+impl FromStr for EvalWeights {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut weights = Self::default();
+
+        for (line_number, line) in s.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let (name, value) = line
+                .split_once('=')
+                .ok_or_else(|| anyhow!("Expected `name = value` on line {}", line_number + 1))?;
+            let name = name.trim();
+            let field = weights
+                .field_mut(name)
+                .ok_or_else(|| anyhow!("Unknown weight {name} on line {}", line_number + 1))?;
+            *field = value
+                .trim()
+                .parse()
+                .with_context(|| format!("Invalid value for {name} on line {}", line_number + 1))?;
+        }
+
+        Ok(weights)
+    }
+}
+
+impl fmt::Display for EvalWeights {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "# Evaluation weights, in hundredths of a man")?;
+        for (name, value) in self.fields() {
+            writeln!(f, "{name} = {value}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Score `board` from the point of view of `player`, who is about to move
+pub fn evaluate(board: &Board, player: Player, weights: &EvalWeights) -> isize {
+    side_score(board, player, weights) - side_score(board, !player, weights) + weights.tempo
+}
+
+/// Everything about the position which is good for `player`
+fn side_score(board: &Board, player: Player, weights: &EvalWeights) -> isize {
+    let size = board.size();
+    let back_row = board.promotion_row(!player);
+    // The middle half of the board in each direction
+    let centre = size / 4..size - size / 4;
+
+    let mut score = 0;
+    let mut score_peice = |idx: usize, kind: TileKind| {
+        let (x, y) = (idx % size, idx / size);
+        if centre.contains(&x) && centre.contains(&y) {
+            score += weights.centre;
+        }
+
+        match kind {
+            TileKind::King => score += weights.king,
+            TileKind::Normal => {
+                score += weights.man;
+                score += weights.advancement * y.abs_diff(back_row) as isize;
+                if y == back_row {
+                    score += weights.back_rank;
+                }
+                if is_runaway(board, player, x, y) {
+                    score += weights.runaway;
+                }
+            }
+        }
+    };
+
+    // English boards only need to visit the squares holding peices
+    match board.bitboard() {
+        Some(bitboard) => {
+            let mut peices = bitboard.peices(player);
+            while peices != 0 {
+                let square = peices.trailing_zeros() as usize;
+                peices &= peices - 1;
+
+                let kind = if bitboard.kings & (1 << square) != 0 {
+                    TileKind::King
+                } else {
+                    TileKind::Normal
+                };
+                score_peice(square_to_idx(square), kind);
+            }
+        }
+        None => {
+            for (idx, tile) in board.board().iter().enumerate() {
+                if tile.is_occupied_by(player) {
+                    score_peice(idx, tile.kind());
+                }
+            }
+        }
+    }
+
+    score + weights.mobility * board.count_legal_moves(player) as isize
+}
+
+/// Check if the man at `x`, `y` has a clear run to the king row, with no
+/// opposing peice able to get in front of it in time
+fn is_runaway(board: &Board, player: Player, x: usize, y: usize) -> bool {
+    let size = board.size();
+    let promotion_row = board.promotion_row(player);
+    let rows = y.abs_diff(promotion_row);
+
+    (1..=rows).all(|distance| {
+        let row = if promotion_row > y {
+            y + distance
+        } else {
+            y - distance
+        };
+        let columns = x.saturating_sub(distance)..=(x + distance).min(size - 1);
+
+        columns
+            .into_iter()
+            .all(|column| !board.board()[row * size + column].is_occupied_by(!player))
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{board::Board, player::Player, r#move::Position};
+
+    use super::{evaluate, is_runaway, EvalWeights};
+
+    #[test]
+    fn test_evaluate() {
+        let weights = EvalWeights::default();
+        let pos = |s: &str| Position::from_str(s).unwrap();
+
+        // The starting position is symmetrical, so only the tempo counts
+        let mut board = Board::new();
+        assert_eq!(evaluate(&board, Player::Black, &weights), weights.tempo);
+        assert_eq!(evaluate(&board, Player::White, &weights), weights.tempo);
+
+        // Losing a man is worse than any positional gain
+        board[pos("c3")].leave();
+        assert!(evaluate(&board, Player::Black, &weights) < -weights.man / 2);
+        assert!(evaluate(&board, Player::White, &weights) > weights.man / 2);
+
+        // A king is worth more than a man
+        let mut man = Board::empty();
+        man[pos("d4")].take_ownership(Player::Black);
+        man[pos("g7")].take_ownership(Player::White);
+        man[pos("h8")].take_ownership(Player::White);
+        let mut king = man;
+        king[pos("d4")].promote();
+        assert!(evaluate(&king, Player::Black, &weights) > evaluate(&man, Player::Black, &weights));
+    }
+
+    #[test]
+    fn test_runaway() {
+        let pos = |s: &str| Position::from_str(s).unwrap();
+        let mut board = Board::empty();
+        board[pos("c5")].take_ownership(Player::Black);
+        assert!(is_runaway(&board, Player::Black, 2, 4));
+
+        // A white man in front of it can block its path
+        board[pos("a7")].take_ownership(Player::White);
+        assert!(!is_runaway(&board, Player::Black, 2, 4));
+
+        // But not once it has been passed
+        board[pos("a7")].leave();
+        board[pos("b4")].take_ownership(Player::White);
+        assert!(is_runaway(&board, Player::Black, 2, 4));
+    }
+
+    #[test]
+    fn test_weights_file() {
+        let weights = EvalWeights {
+            king: 150,
+            tempo: -3,
+            ..EvalWeights::default()
+        };
+        assert_eq!(weights.to_string().parse::<EvalWeights>().unwrap(), weights);
+
+        let weights: EvalWeights = "# Prefer kings\nking = 200 # a comment\n\nmobility=0\n"
+            .parse()
+            .unwrap();
+        assert_eq!(weights.king, 200);
+        assert_eq!(weights.mobility, 0);
+        assert_eq!(weights.man, EvalWeights::default().man);
+
+        assert!("queen = 900".parse::<EvalWeights>().is_err());
+        assert!("king: 200".parse::<EvalWeights>().is_err());
+        assert!("king = lots".parse::<EvalWeights>().is_err());
+    }
+}
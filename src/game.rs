@@ -1,29 +1,47 @@
 use clearscreen::clear;
-use dialoguer::Select;
-use rand::Rng;
-use slab_tree::{Tree, TreeBuilder, self};
-use std::{collections::HashMap, io::stdin, sync::PoisonError};
+use dialoguer::{Confirm, Input, Select};
+use rand::{seq::SliceRandom, Rng};
+use std::{fs, sync::Arc, time::Instant};
 
 
-use anyhow::{anyhow, Context, Error, Result};
+use anyhow::{anyhow, Context, Result};
 
 use crate::{
-    board::{self, *},
-    king_moves::KING_MOVES,
-    player::{self, *},
-    r#move::{Move, Position},
+    ballot::Ballot,
+    board::*,
+    book::OpeningBook,
+    clock::Clock,
+    eval::EvalWeights,
+    player::*,
+    r#move::{Move, MoveDisplay, Notation},
+    rules::Variant,
+    difficulty::{AiSettings, Engine},
+    history::PositionHistory,
+    mcts::{Mcts, MctsConfig, MctsLimit},
+    pdn::{self, PdnGame, PdnMove, PdnResult},
+    result::{DrawReason, GameResult, WinReason},
+    save::SaveFile,
+    search::{SearchConfig, Searcher},
+    tablebase::Tablebase,
     tile::*,
 };
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum GameMode {
     HumanVsHuman,
-    HumanVsAi,
-    AiVsAi,
+    /// The human moves first, against an AI with these settings
+    HumanVsAi(AiSettings),
+    /// The AIs playing as the first and second player
+    AiVsAi(AiSettings, AiSettings),
 }
 
 impl GameMode {
+    const NAMES: [&'static str; 3] = ["Human vs Human", "Human vs AI", "AI vs AI"];
+    /// Short names which never change, used in save files
+    const KEYS: [&'static str; 3] = ["human-human", "human-ai", "ai-ai"];
+
     pub fn select_gamemode() -> Result<Self> {
-        let options = vec!["Human vs Human", "Human vs AI", "AI vs AI"];
+        let options = Self::NAMES;
         let selection = Select::new()
             .with_prompt("Select a gamemode (use the arrow keys to make your selection)")
             .items(&options)
@@ -31,187 +49,712 @@ impl GameMode {
 
         Ok(match selection {
             0 => Self::HumanVsHuman,
-            1 => Self::HumanVsAi,
-            2 => Self::AiVsAi,
+            1 => Self::HumanVsAi(AiSettings::select("AI")?),
+            2 => Self::AiVsAi(
+                AiSettings::select("first AI")?,
+                AiSettings::select("second AI")?,
+            ),
             _ => unreachable!(),
         })
     }
+
+    /// The settings of the AI making move number `move_id`, or `None` if it
+    /// is a humans turn
+    pub fn ai_for_move(&self, move_id: usize) -> Option<AiSettings> {
+        let first = move_id.is_multiple_of(2);
+        match *self {
+            GameMode::HumanVsHuman => None,
+            GameMode::HumanVsAi(_) if first => None,
+            GameMode::HumanVsAi(ai) => Some(ai),
+            GameMode::AiVsAi(ai, _) if first => Some(ai),
+            GameMode::AiVsAi(_, ai) => Some(ai),
+        }
+    }
+
+    /// The name of the mode, leaving out the settings of any AIs
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameMode::HumanVsHuman => Self::NAMES[0],
+            GameMode::HumanVsAi(_) => Self::NAMES[1],
+            GameMode::AiVsAi(_, _) => Self::NAMES[2],
+        }
+    }
+
+    /// The settings of every AI in the game, in the order they play
+    pub fn ais(&self) -> Vec<AiSettings> {
+        match *self {
+            GameMode::HumanVsHuman => vec![],
+            GameMode::HumanVsAi(ai) => vec![ai],
+            GameMode::AiVsAi(first, second) => vec![first, second],
+        }
+    }
+
+    /// The key of the mode, see `KEYS`
+    pub fn key(&self) -> &'static str {
+        match self {
+            GameMode::HumanVsHuman => Self::KEYS[0],
+            GameMode::HumanVsAi(_) => Self::KEYS[1],
+            GameMode::AiVsAi(_, _) => Self::KEYS[2],
+        }
+    }
+
+    /// Build a mode from its name or key and the settings of its AIs, the
+    /// reverse of `name` and `ais`
+    pub fn from_name(name: &str, ais: &[AiSettings]) -> Result<Self> {
+        let name = name.trim();
+        let position = Self::NAMES
+            .iter()
+            .position(|n| n.eq_ignore_ascii_case(name))
+            .or_else(|| Self::KEYS.iter().position(|k| k.eq_ignore_ascii_case(name)));
+        let mode = match (position, ais) {
+            (Some(0), []) => GameMode::HumanVsHuman,
+            (Some(1), [ai]) => GameMode::HumanVsAi(*ai),
+            (Some(2), [first, second]) => GameMode::AiVsAi(*first, *second),
+            (Some(_), _) => {
+                let count = ais.len();
+                return Err(anyhow!("Wrong number of AIs for {name}, found {count}"));
+            }
+            (None, _) => return Err(anyhow!("Unknown game mode {name}")),
+        };
+        Ok(mode)
+    }
+
+    /// A name for the player making move number `move_id`, used when
+    /// recording games
+    pub fn player_name(&self, move_id: usize) -> String {
+        match self.ai_for_move(move_id) {
+            Some(ai) => format!("AI ({}, {})", ai.difficulty, ai.engine),
+            None => "Human".to_string(),
+        }
+    }
+}
+
+/// What a player chose to do on their turn
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    Move(Move),
+    /// Take back the players last move, along with the reply to it. Against
+    /// another human this needs their agreement.
+    Undo,
+    /// Ask to take back the move just made, before the other player replies.
+    /// Only possible when two humans are playing, and the other player has to
+    /// agree.
+    TakeBack,
+    /// Play a move taken back by `Undo` again, along with the reply to it
+    Redo,
+    /// Offer the other player a draw, which ends the game if they accept
+    OfferDraw,
+    Resign,
+    /// Save the game to a file and stop playing
+    SaveAndQuit(String),
+}
+
+/// What happens once a players action has been carried out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Next {
+    Play,
+    Finish(GameResult),
+    /// The game was saved to be finished later
+    Quit,
+}
+
+/// Put a yes or no question to a human player
+fn ask_player(question: &str) -> Result<bool> {
+    let answer = Confirm::new()
+        .with_prompt(question)
+        .default(false)
+        .interact()?;
+    Ok(answer)
+}
+
+/// Ask the opponent of `player` whether `player` may take back their moves
+fn takeback_question(player: Player) -> String {
+    format!("{}, allow {player} to take back their last move?", !player)
 }
 
 pub struct Game {
     board: Board,
+    /// The position the game started from
+    start: Board,
     move_id: usize,
     mode: GameMode,
     moves: Vec<Move>,
+    /// The board before each move which can be taken back, most recent last
+    history: Vec<Board>,
+    /// Moves which have been taken back, ready to be played again, most
+    /// recently taken back last
+    undone: Vec<Move>,
+    /// Every position reached so far, for the draw rules
+    positions: PositionHistory,
+    /// How the game ended, once it is over
+    result: Option<GameResult>,
+    /// Each players time, in timed games
+    clock: Option<Clock>,
+    /// The number of quiet moves each player can make before the game is
+    /// drawn, if not the limit of the variant
+    move_limit: Option<usize>,
+    search: SearchConfig,
+    mcts: MctsConfig,
+    tablebase: Option<Arc<Tablebase>>,
+    book: Option<Arc<OpeningBook>>,
+    notation: Notation,
+    /// The files the AIs weights, tablebase and book were loaded from, so a
+    /// saved game can load them again
+    files: LoadedFiles,
+}
+
+/// Files are kept by their full paths, so a saved game can find them again
+/// when it is resumed from another directory
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct LoadedFiles {
+    weights: Option<String>,
+    tablebase: Option<String>,
+    book: Option<String>,
+}
+
+/// The full path to a file which has just been loaded, see `LoadedFiles`
+fn full_path(path: &str) -> Result<Option<String>> {
+    let full_path = fs::canonicalize(path)
+        .with_context(|| format!("Failed to find the full path to {path}"))?;
+    Ok(Some(full_path.display().to_string()))
 }
 
 impl Game {
     pub fn new() -> Self {
+        let mode = GameMode::select_gamemode().unwrap_or(GameMode::HumanVsHuman);
+        let variant = Variant::select_variant().unwrap_or_default();
+        Self::with_variant(variant, mode)
+    }
+
+    pub fn with_variant(variant: Variant, mode: GameMode) -> Self {
+        let board = Board::with_variant(variant);
         Self {
-            board: Board::new(),
+            board,
+            start: board,
             move_id: 0,
-            mode: GameMode::select_gamemode().unwrap_or(GameMode::HumanVsHuman),
+            mode,
             moves: Vec::new(),
+            history: Vec::new(),
+            undone: Vec::new(),
+            positions: PositionHistory::new(&board, board.rules().first_player()),
+            result: None,
+            clock: None,
+            move_limit: None,
+            search: SearchConfig::default(),
+            mcts: MctsConfig::default(),
+            tablebase: None,
+            book: None,
+            notation: Notation::default(),
+            files: LoadedFiles::default(),
+        }
+    }
+
+    /// Start an English draughts game from a ballot, with its opening moves
+    /// already played
+    pub fn with_ballot(ballot: &Ballot, mode: GameMode) -> Self {
+        let board = ballot.board();
+        Self {
+            board,
+            start: ballot.setup(),
+            positions: PositionHistory::new(&board, ballot.player_to_move()),
+            move_id: ballot.moves().len(),
+            moves: ballot.moves().to_vec(),
+            ..Self::with_variant(Variant::English, mode)
+        }
+    }
+
+    /// Continue a game from a game record, with every move of its main line
+    /// already played. Unlike ballots, these moves can be taken back.
+    pub fn from_pdn(game: &PdnGame, mode: GameMode) -> Result<Self> {
+        let (start, player) = game.setup()?;
+        let (_, moves) = game.replay()?;
+        let mut new_game = Self::from_position(start, player, mode);
+        for this_move in moves {
+            new_game.play_move(this_move)?;
+        }
+        Ok(new_game)
+    }
+
+    /// Start a game from a position in PDN-FEN, see `Board::from_fen`
+    pub fn from_fen(variant: Variant, fen: &str, mode: GameMode) -> Result<Self> {
+        let (board, player) = Board::from_fen(variant, fen)?;
+        Ok(Self::from_position(board, player, mode))
+    }
+
+    /// Start a game from any position with `player` to move
+    pub fn from_position(board: Board, player: Player, mode: GameMode) -> Self {
+        // Moves are numbered from the first player, so skip a move when the
+        // second player is to move
+        let move_id = usize::from(player != board.rules().first_player());
+        Self {
+            board,
+            start: board,
+            move_id,
+            positions: PositionHistory::new(&board, player),
+            ..Self::with_variant(board.variant(), mode)
+        }
+    }
+
+    /// Record the game so far in Portable Draughts Notation
+    pub fn to_pdn(&self) -> PdnGame {
+        let variant = self.board.variant();
+        let mut game = PdnGame::default();
+        game.set_tag("Event", "Casual game");
+        for move_id in 0..2 {
+            let colour = self.board.player_for_turn(move_id).to_string();
+            game.set_tag(&colour, self.mode.player_name(move_id));
         }
+        game.result = match &self.result {
+            Some(result) => PdnResult::from_game_result(result, self.board.rules().first_player()),
+            None => PdnResult::from_board(&self.board, self.get_current_player()),
+        };
+        game.set_tag("Result", game.result.to_string());
+        game.set_tag("GameType", pdn::game_type(variant).to_string());
+        let first_move_id = self.move_id - self.moves.len();
+        if self.start != Board::with_variant(variant) || !first_move_id.is_multiple_of(2) {
+            let player = self.start.player_for_turn(first_move_id);
+            game.set_tag("SetUp", "1");
+            game.set_tag("FEN", self.start.fen(player));
+        }
+        game.line.moves = self
+            .moves
+            .iter()
+            .map(|this_move| PdnMove::from_move(this_move, variant))
+            .collect();
+        game
     }
 
     pub fn from_board(board: &[Tile; 64], mode: GameMode) -> Self {
         let mut b = Board::empty();
-        *b.board_mut() = *board;
+        b.board_mut().copy_from_slice(board);
         Self {
             board: b,
+            start: b,
+            positions: PositionHistory::new(&b, b.rules().first_player()),
             move_id: 0,
             mode,
             moves: Vec::new(),
+            history: Vec::new(),
+            undone: Vec::new(),
+            result: None,
+            clock: None,
+            move_limit: None,
+            search: SearchConfig::default(),
+            mcts: MctsConfig::default(),
+            tablebase: None,
+            book: None,
+            notation: Notation::default(),
+            files: LoadedFiles::default(),
         }
     }
 
     pub fn get_current_player(&self) -> Player {
-        if self.move_id % 2 == 0 {
-            Player::Black
-        } else {
-            Player::White
-        }
+        self.board.player_for_turn(self.move_id)
     }
 
-    #[allow(clippy::clone_on_copy)]
     /// This function looks at the current board and determines the best possible move it can make in that given state.
-    /// In order to figure this out, it uses the negamax algorithm, which is a variant of the minimax algorithm.  
+    /// In order to figure this out, it uses the negamax algorithm, which is a variant of the minimax algorithm,
+    /// with alpha-beta pruning to skip moves which cannot change the outcome.
     pub fn get_best_move(&self) -> Result<Move> {
-        let mut search_space = TreeBuilder::new().with_root((self.board, self.board.evaluate_board(self.get_current_player()))).build();
-        let moves = self.generate_all_possible_moves(self.board, self.get_current_player())?;
-        let move_evaluations: HashMap<Move, isize> = HashMap::from_iter(moves.iter().map(|potential_move| (*potential_move, self.evaluate_move(*potential_move).unwrap())));
-        let mut root = search_space.root_mut().unwrap();
-        move_evaluations.iter().for_each(|possible_move| {
-            let (possible_move, evaluation) = possible_move;
-            let mut board_cpy = self.board.clone(); 
-            board_cpy.make_move(self.move_id, *possible_move).unwrap();
-            root.append((board_cpy, *evaluation));
-        });
-
-        let mut rng = rand::thread_rng(); 
-        let best_move = move_evaluations
-            .iter()
-            .max_by(|me_lhs, me_rhs| me_lhs.1.cmp(me_rhs.1));
+        let ai = self.mode.ai_for_move(self.move_id).unwrap_or_default();
+        let player = self.get_current_player();
 
-        let best_move = match best_move {
-            Some(m) => *m.0, 
-            None => {
-                if moves.is_empty() {
-                    return Err(anyhow!("No moves remain!"));
-                }
+        // Play straight from the opening book while the game is still in it
+        let mut rng = rand::thread_rng();
+        if let Some(book_move) = self
+            .book
+            .as_ref()
+            .and_then(|book| book.probe(&self.board, player, &mut rng))
+        {
+            println!("AI played book move {}", self.show(&book_move));
+            return Ok(book_move);
+        }
 
-                let idx = rng.gen_range(0..moves.len());
+        // Weaker AIs sometimes play a random move instead of thinking
+        if rng.gen_bool(ai.difficulty.blunder_rate()) {
+            if let Some(blunder) = self.board.get_legal_moves(player).choose(&mut rng) {
+                println!("AI chose move {}", self.show(blunder));
+                return Ok(blunder.clone());
+            }
+        }
 
-                *moves.get(idx).unwrap()
+        // In timed games the AI thinks on its own clock
+        let mut search = SearchConfig {
+            move_limit: Some(self.move_limit()),
+            ..self.search
+        };
+        let mut mcts = ai.mcts_config(self.mcts);
+        if let Some(clock) = &self.clock {
+            search.time = clock.time_control(player);
+            if let Some(budget) = search.time.budget() {
+                mcts.limit = MctsLimit::Time(budget);
             }
-        }; 
+        }
 
-        println!("AI chose move {best_move}"); 
+        if ai.engine == Engine::MonteCarlo {
+            let result = Mcts::new(mcts).search(&self.board, player);
+            let best_move = result.best_move.ok_or_else(|| anyhow!("No moves remain!"))?;
+            println!(
+                "AI chose move {} (won {:.0}% of {} playouts)",
+                self.show(&best_move),
+                result.win_rate * 100.0,
+                result.iterations
+            );
+            return Ok(best_move);
+        }
 
-        Ok(best_move)
-    }
+        let mut searcher = Searcher::new(ai.search_config(search));
+        if let Some(tablebase) = &self.tablebase {
+            searcher = searcher.with_tablebase(tablebase.clone());
+        }
+        let result = searcher
+            .with_history(self.positions.clone())
+            .search(&self.board, player);
 
-    pub fn generate_all_possible_moves(&self, board: Board, moving_player: Player) -> Result<Vec<Move>> {
-        let friendly_peices = board.get_idx_of_player_peices(moving_player);
+        let best_move = result.best_move.ok_or_else(|| anyhow!("No moves remain!"))?;
 
-        Ok(friendly_peices
+        let principal_variation: Vec<String> = result
+            .principal_variation
             .iter()
-            .flat_map(|peice| self.generate_moves_for_peice(*peice).unwrap())
-            .collect())
+            .map(|m| self.show(m).to_string())
+            .collect();
+        println!(
+            "AI chose move {} (score {}, depth {})",
+            self.show(&best_move),
+            result.score, result.depth
+        );
+        println!("Expected line: {}", principal_variation.join(", "));
+
+        Ok(best_move)
     }
 
-    pub fn generate_moves_for_peice(&self, peice: Position) -> Result<Vec<Move>> {
-        let selected_peice = self.board[peice];
-        let mut potential_moves = Vec::new();
+    /// Play the game until it is over, returning the result, or `None` if
+    /// the game was saved to be finished later
+    pub fn run(&mut self) -> Result<Option<GameResult>> {
+        loop {
+            if let Some(result) = self.game_over() {
+                return Ok(Some(self.finish(result)));
+            }
+            let moving_player = self.get_current_player();
 
-        if selected_peice.is_empty() {
-            return Err(anyhow!("Selected peice is empty!"));
-        }
+            if let GameMode::HumanVsHuman = self.mode {
+                clear()?;
+            };
 
-        match selected_peice.kind {
-            TileKind::Normal => {
-                let (x, y) = peice.coords();
-                let y_offset: isize = if let Player::Black = selected_peice.occupied_by.unwrap() {
-                    1
-                } else {
-                    -1
-                };
+            println!("{}", self.board);
+            self.get_stats();
+            // The game isn't over, so the AI always has a move to find
+            let started = Instant::now();
+            let action = match self.mode.ai_for_move(self.move_id) {
+                Some(_) => Action::Move(self.get_best_move()?),
+                // Errors come from the terminal, and asking again won't fix them
+                None => self
+                    .get_user_action()
+                    .context("Failed to read the players choice")?,
+            };
 
-                if let Ok(pos) = Position::from_coords_checked(x as isize + 1, y as isize + y_offset) {
-                    potential_moves.push(Move::new(peice.idx(), pos.idx()))
+            if let Some(clock) = &mut self.clock {
+                clock.spend(moving_player, started.elapsed());
+                if clock.is_flagged(moving_player) {
+                    let result = GameResult::Win {
+                        winner: !moving_player,
+                        reason: WinReason::TimeForfeit,
+                    };
+                    return Ok(Some(self.finish(result)));
                 }
+            }
 
-                if let Ok(pos) =  Position::from_coords_checked(x as isize - 1, y as isize + y_offset) {
-                    potential_moves.push(Move::new(peice.idx(), pos.idx()))
-                }
+            let moved = matches!(action, Action::Move(_));
+            match self.handle_action(moving_player, action, &mut ask_player)? {
+                Next::Play => (),
+                Next::Finish(result) => return Ok(Some(self.finish(result))),
+                Next::Quit => return Ok(None),
             }
-            TileKind::King => {
-                let moves = KING_MOVES[peice.idx()].to_vec();
-                for king_move in moves {
-                    potential_moves.push(Move::new(peice.idx(), king_move));
+
+            // Between two humans, the player who just moved can ask to take
+            // their move back before the other player replies
+            if moved && self.can_take_back() {
+                let question = format!("{moving_player}, ask to take back that move?");
+                if ask_player(&question)? {
+                    self.handle_action(moving_player, Action::TakeBack, &mut ask_player)?;
                 }
             }
         }
-
-        Ok(potential_moves
-            .iter()
-            .filter(|m| self.is_valid_move(**m))
-            .copied()
-            .collect())
     }
 
-
-    pub fn run(&mut self) -> Result<Player> {
-        loop {
-            if self.move_id >= 2 * (12 * 12) {
-                println!("Too many moves");
-                break Err(anyhow!("Too many moves"));
+    /// Carry out the action `player` chose on their turn, or just after it
+    /// for `Action::TakeBack`. `ask` puts a yes or no question to a human
+    /// player, such as whether they agree to a takeback.
+    fn handle_action(
+        &mut self,
+        player: Player,
+        action: Action,
+        ask: &mut dyn FnMut(&str) -> Result<bool>,
+    ) -> Result<Next> {
+        match action {
+            Action::Move(this_move) => {
+                self.play_move(this_move)?;
+                if let Some(clock) = &mut self.clock {
+                    clock.finish_move(player);
+                }
             }
-            let moving_player = self.get_current_player();
-            match (
-                self.board.get_remaining_peices(Player::Black),
-                self.board.get_remaining_peices(Player::White),
-            ) {
-                (0, _) => {
-                    return Ok(Player::Black);
+            Action::Undo => {
+                if matches!(self.mode, GameMode::HumanVsHuman)
+                    && !ask(&takeback_question(player))?
+                {
+                    println!("The takeback was declined");
+                    return Ok(Next::Play);
                 }
-                (_, 0) => {
-                    return Ok(Player::White);
+                if let Err(e) = self.undo_turn() {
+                    println!("{e:#}");
                 }
-                (_, _) => (),
-            };
+            }
+            Action::TakeBack => {
+                // Only the last move can be taken back, by the player who made it
+                if !self.can_take_back() || self.get_current_player() == player {
+                    println!("{player} has no move to take back");
+                } else if ask(&takeback_question(player))? {
+                    self.undo()?;
+                } else {
+                    println!("The takeback was declined");
+                }
+            }
+            Action::Redo => {
+                if let Err(e) = self.redo_turn() {
+                    println!("{e:#}");
+                }
+            }
+            Action::OfferDraw => {
+                if self.accept_draw(ask)? {
+                    return Ok(Next::Finish(GameResult::Draw(DrawReason::Agreement)));
+                }
+                println!("The draw offer was declined");
+            }
+            Action::Resign => {
+                return Ok(Next::Finish(GameResult::Win {
+                    winner: !player,
+                    reason: WinReason::Resignation,
+                }));
+            }
+            Action::SaveAndQuit(path) => match self.save(&path) {
+                Ok(()) => {
+                    println!("Game saved, continue it with `checkers resume {path}`");
+                    return Ok(Next::Quit);
+                }
+                // Keep playing rather than lose the game
+                Err(e) => println!("{e:#}"),
+            },
+        }
+        Ok(Next::Play)
+    }
 
-            if let GameMode::HumanVsHuman = self.mode {
-                clear()?;
-            };
+    /// The result of the game if it is over in the current position
+    pub fn game_over(&self) -> Option<GameResult> {
+        for player in [Player::Black, Player::White] {
+            if self.board.get_remaining_peices(player) == 0 {
+                return Some(GameResult::Win {
+                    winner: !player,
+                    reason: WinReason::NoPeices,
+                });
+            }
+        }
 
-            println!("{}", self.board);
-            self.get_stats();
-            let this_move = loop {
-                let this_move = match self.mode {
-                    GameMode::HumanVsHuman => self.get_user_move(),
-                    GameMode::HumanVsAi if self.move_id % 2 == 0 => self.get_user_move(),
-                    GameMode::HumanVsAi => self.get_best_move(),
-                    GameMode::AiVsAi => self.get_best_move(),
-                };
-
-                if let Ok(m) = this_move {
-                    break m;
-                } 
+        // A player who can't move loses, even with peices left
+        let player = self.get_current_player();
+        if self.board.get_legal_moves(player).is_empty() {
+            return Some(GameResult::Win {
+                winner: !player,
+                reason: WinReason::NoLegalMoves,
+            });
+        }
 
-            };
-            self.moves.push(this_move);
-            let opp_count_before_move = self.board.get_remaining_peices(!moving_player);
-            self.board.make_move(self.move_id, this_move)?;
-            let opp_count_after_move = self.board.get_remaining_peices(!moving_player);
-
-            self.move_id += if opp_count_after_move <= opp_count_before_move {
-                1
-            } else {
-                2
-            }
+        self.positions
+            .draw(self.board.rules(), self.move_limit())
+            .map(GameResult::Draw)
+    }
+
+    /// End the game with `result`
+    fn finish(&mut self, result: GameResult) -> GameResult {
+        self.result = Some(result);
+        result
+    }
+
+    /// How the game ended, or `None` if it is still being played
+    pub fn result(&self) -> Option<GameResult> {
+        self.result
+    }
+
+    /// Play a move for the current player. Capture chains are played out in
+    /// full by a single move, so the turn always passes to the other player
+    /// afterwards. Any moves which were taken back can no longer be redone.
+    pub fn play_move(&mut self, this_move: Move) -> Result<()> {
+        self.push_move(this_move)?;
+        self.undone.clear();
+        Ok(())
+    }
+
+    fn push_move(&mut self, this_move: Move) -> Result<()> {
+        let board = self.board;
+        self.board.make_move(self.move_id, this_move.clone())?;
+        let player = board.player_for_turn(self.move_id);
+        self.positions.push(&board, player, &this_move);
+        self.history.push(board);
+        self.moves.push(this_move);
+        self.move_id += 1;
+        Ok(())
+    }
+
+    /// Take back the last move, returning it
+    pub fn undo(&mut self) -> Result<Move> {
+        let board = self
+            .history
+            .pop()
+            .ok_or_else(|| anyhow!("There are no moves to take back"))?;
+        // SAFETY: every board in the history was followed by a move
+        let this_move = self.moves.pop().unwrap();
+        self.board = board;
+        self.move_id -= 1;
+        self.positions.pop();
+        self.result = None;
+        self.undone.push(this_move.clone());
+        Ok(this_move)
+    }
+
+    /// Play the last move taken back again, returning it
+    pub fn redo(&mut self) -> Result<Move> {
+        let this_move = self
+            .undone
+            .pop()
+            .ok_or_else(|| anyhow!("There are no moves to redo"))?;
+        self.push_move(this_move.clone())?;
+        Ok(this_move)
+    }
+
+    /// Whether the current player can take back their last move
+    pub fn can_undo_turn(&self) -> bool {
+        self.history.len() >= 2
+    }
+
+    /// Whether the current player can play their next move again, after it
+    /// was taken back
+    pub fn can_redo_turn(&self) -> bool {
+        self.undone.len() >= 2
+    }
+
+    /// Go back to the current players previous turn, taking back their last
+    /// move and the reply to it
+    pub fn undo_turn(&mut self) -> Result<()> {
+        if !self.can_undo_turn() {
+            return Err(anyhow!("There are no earlier turns to go back to"));
+        }
+        self.undo()?;
+        self.undo()?;
+        Ok(())
+    }
+
+    /// Go forward to the current players next turn, undoing `undo_turn`
+    pub fn redo_turn(&mut self) -> Result<()> {
+        if !self.can_redo_turn() {
+            return Err(anyhow!("There are no later turns to go forward to"));
+        }
+        self.redo()?;
+        self.redo()?;
+        Ok(())
+    }
+
+    /// Ask the opponent whether they accept a draw. An AI accepts unless it
+    /// thinks it is winning.
+    fn accept_draw(&self, ask: &mut dyn FnMut(&str) -> Result<bool>) -> Result<bool> {
+        let player = self.get_current_player();
+        let Some(ai) = self.mode.ai_for_move(self.move_id + 1) else {
+            return ask(&format!("{}, {player} offers a draw. Accept?", !player));
+        };
+
+        let mut searcher = Searcher::new(ai.search_config(self.search));
+        if let Some(tablebase) = &self.tablebase {
+            searcher = searcher.with_tablebase(tablebase.clone());
+        }
+        // The score is for the player offering the draw
+        let result = searcher
+            .with_history(self.positions.clone())
+            .search(&self.board, player);
+        Ok(result.score >= 0)
+    }
+
+    /// Whether the player who just moved can ask to take their move back,
+    /// which is only possible when two humans are playing
+    pub fn can_take_back(&self) -> bool {
+        matches!(self.mode, GameMode::HumanVsHuman) && !self.history.is_empty()
+    }
+
+    /// Everything needed to carry on the game later
+    pub fn save_file(&self) -> SaveFile {
+        let variant = self.board.variant();
+        let first_move_id = self.move_id - self.moves.len();
+        let threads = self.search.threads;
+        SaveFile {
+            variant,
+            mode: self.mode,
+            start: self.start.fen(self.start.player_for_turn(first_move_id)),
+            moves: self
+                .moves
+                .iter()
+                .map(|m| m.display(Notation::Numeric, variant).to_string())
+                .collect(),
+            move_limit: self.move_limit,
+            clock: self.clock,
+            notation: self.notation,
+            // Only a thread count which was chosen is kept, so the game
+            // still uses every core when resumed on another computer
+            threads: (threads != SearchConfig::default().threads).then_some(threads),
+            weights: self.files.weights.clone(),
+            tablebase: self.files.tablebase.clone(),
+            book: self.files.book.clone(),
+        }
+    }
+
+    /// Restore a saved game, replaying every move from the start and loading
+    /// any files the AI was using again
+    pub fn from_save_file(save: &SaveFile) -> Result<Self> {
+        let mut game = Self::from_fen(save.variant, &save.start, save.mode)?;
+        game.move_limit = save.move_limit;
+        game.clock = save.clock;
+        game.notation = save.notation;
+        if let Some(threads) = save.threads {
+            game.search.threads = threads;
+        }
+        if let Some(path) = &save.weights {
+            game.load_weights(path)?;
+        }
+        if let Some(path) = &save.tablebase {
+            game.load_tablebase(path)?;
+        }
+        if let Some(path) = &save.book {
+            game.load_book(path)?;
         }
+
+        for text in &save.moves {
+            let this_move = game.board.parse_move(game.get_current_player(), text)?;
+            game.play_move(this_move)?;
+        }
+        Ok(game)
+    }
+
+    pub fn save<P: AsRef<std::path::Path>>(&self, path: P) -> Result<()> {
+        self.save_file().save(path)
+    }
+
+    pub fn resume<P: AsRef<std::path::Path>>(path: P) -> Result<Self> {
+        Self::from_save_file(&SaveFile::load(path)?)
+    }
+
+    pub fn mode(&self) -> GameMode {
+        self.mode
+    }
+
+    /// Every move played so far
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
     }
 
     pub fn board(&self) -> &Board {
@@ -222,6 +765,77 @@ impl Game {
         &mut self.board
     }
 
+    /// The settings used when the AI searches for a move
+    pub fn search_config_mut(&mut self) -> &mut SearchConfig {
+        &mut self.search
+    }
+
+    /// Play with a clock, which is shared between the moves of each player
+    pub fn set_clock(&mut self, clock: Clock) {
+        self.clock = Some(clock);
+    }
+
+    pub fn clock(&self) -> Option<&Clock> {
+        self.clock.as_ref()
+    }
+
+    /// Draw the game once each player has made `limit` moves in a row
+    /// without capturing or moving a man
+    pub fn set_move_limit(&mut self, limit: usize) {
+        self.move_limit = Some(limit);
+    }
+
+    pub fn move_limit(&self) -> usize {
+        self.move_limit.unwrap_or_else(|| self.board.rules().draw_move_limit())
+    }
+
+    /// Let the alpha-beta AI play endgames perfectly using `tablebase`
+    pub fn set_tablebase(&mut self, tablebase: Arc<Tablebase>) {
+        self.tablebase = Some(tablebase);
+    }
+
+    /// Load a tablebase from a file, see `set_tablebase`
+    pub fn load_tablebase(&mut self, path: &str) -> Result<()> {
+        self.set_tablebase(Arc::new(Tablebase::load(path)?));
+        self.files.tablebase = full_path(path)?;
+        Ok(())
+    }
+
+    /// Load evaluation weights from a file, used by both kinds of AI
+    pub fn load_weights(&mut self, path: &str) -> Result<()> {
+        let weights = EvalWeights::load(path)?;
+        self.search.weights = weights;
+        self.mcts.weights = weights;
+        self.files.weights = full_path(path)?;
+        Ok(())
+    }
+
+    /// Choose how moves are written when showing them to the players
+    pub fn set_notation(&mut self, notation: Notation) {
+        self.notation = notation;
+    }
+
+    fn show<'a>(&self, this_move: &'a Move) -> MoveDisplay<'a> {
+        this_move.display(self.notation, self.board.variant())
+    }
+
+    /// Let the AI play its opening moves from `book`
+    pub fn set_book(&mut self, book: Arc<OpeningBook>) {
+        self.book = Some(book);
+    }
+
+    /// Load an opening book from a file, see `set_book`
+    pub fn load_book(&mut self, path: &str) -> Result<()> {
+        self.set_book(Arc::new(OpeningBook::load(path)?));
+        self.files.book = full_path(path)?;
+        Ok(())
+    }
+
+    /// The settings used when an AI plays with Monte Carlo tree search
+    pub fn mcts_config_mut(&mut self) -> &mut MctsConfig {
+        &mut self.mcts
+    }
+
     pub fn get_stats(&self) {
         println!("Move No #{}", self.move_id + 1);
         println!("Remaining Peices:");
@@ -231,34 +845,329 @@ impl Game {
             self.board.get_remaining_peices(Player::Black),
             self.board.get_remaining_peices(Player::White)
         );
+        if let Some(clock) = &self.clock {
+            println!("Time left: {clock}");
+        }
     }
 
-    pub fn get_user_move(&self) -> Result<Move> {
+    /// Ask the current player for their move, or for another action such as
+    /// taking back a move, resigning or saving and quitting
+    pub fn get_user_action(&self) -> Result<Action> {
         println!("Moving player: {}", self.get_current_player());
-        let valid_moves = self.generate_all_possible_moves(self.board, self.get_current_player())?;
+        let valid_moves = self.board.get_legal_moves(self.get_current_player());
+        let mut options: Vec<String> = valid_moves
+            .iter()
+            .map(|m| self.show(m).to_string())
+            .collect();
+        let mut actions = Vec::new();
+        if self.can_undo_turn() {
+            let undo = match self.mode {
+                GameMode::HumanVsHuman => "Request a takeback",
+                _ => "Undo",
+            };
+            actions.push((undo.to_string(), Action::Undo));
+        }
+        if self.can_redo_turn() {
+            actions.push(("Redo".to_string(), Action::Redo));
+        }
+        actions.push(("Offer a draw".to_string(), Action::OfferDraw));
+        actions.push(("Resign".to_string(), Action::Resign));
+        let save = Action::SaveAndQuit(String::new());
+        actions.push(("Save and quit".to_string(), save));
+        options.extend(actions.iter().map(|(name, _)| name.clone()));
+
         let selection = Select::new()
             .with_prompt("Select a move (use arrow keys to make your selection)")
-            .items(&valid_moves)
+            .items(&options)
             .interact()?;
-        Ok(*valid_moves.get(selection).unwrap())
+
+        if let Some(this_move) = valid_moves.get(selection) {
+            return Ok(Action::Move(this_move.clone()));
+        }
+        match actions.swap_remove(selection - valid_moves.len()).1 {
+            Action::SaveAndQuit(_) => {
+                let path: String = Input::new()
+                    .with_prompt("Save the game to")
+                    .default("checkers.save".to_string())
+                    .interact_text()?;
+                Ok(Action::SaveAndQuit(path))
+            }
+            action => Ok(action),
+        }
     }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
 
-    #[allow(clippy::clone_on_copy)]
-    // Lint disabled here as we need to make a deep copy to prevent the game
-    // from actually making the move before we know if its valid or not
-    fn is_valid_move(&self, this_move: Move) -> bool {
-        let mut board_cpy = self.board.clone();
-        board_cpy.make_move(self.move_id, this_move).is_ok()
+    use crate::{
+        ballot::Deck,
+        board::Board,
+        clock::Clock,
+        difficulty::{AiSettings, Difficulty},
+        pdn::{PdnGame, PdnResult},
+        player::Player,
+        r#move::{Notation, Position},
+        result::{DrawReason, GameResult, WinReason},
+        rules::Variant,
+    };
+
+    use super::{Action, Game, GameMode};
+
+    fn play(game: &mut Game, moves: &[&str]) {
+        for text in moves {
+            let player = game.get_current_player();
+            let this_move = game.board().parse_move(player, text).unwrap();
+            game.play_move(this_move).unwrap();
+        }
     }
 
+    #[test]
+    fn test_undo_and_redo() {
+        let mut game = Game::with_variant(Variant::English, GameMode::HumanVsHuman);
+        let start = *game.board();
+        play(&mut game, &["c3-d4", "f6-e5"]);
+        let before_capture = *game.board();
+        play(&mut game, &["d4xf6"]);
+        let after_capture = *game.board();
+
+        assert_eq!(game.undo().unwrap().notation(), "d4xf6");
+        assert_eq!(game.board(), &before_capture);
+        assert_eq!(game.get_current_player(), Player::Black);
+        assert_eq!(game.moves().len(), 2);
 
-    #[allow(clippy::clone_on_copy)]
-    // See Game::is_valid move for explaination of why I'm disabling the lint 
-    fn evaluate_move(&self, this_move: Move) -> Result<isize> {
-        let mut board_cpy = self.board.clone(); 
-        board_cpy.make_move(self.move_id, this_move)?;
-        Ok(board_cpy.evaluate_board(!self.get_current_player()))
+        assert_eq!(game.redo().unwrap().notation(), "d4xf6");
+        assert_eq!(game.board(), &after_capture);
+        assert!(game.redo().is_err());
+
+        // A turn is the players own move and the reply to it
+        game.undo().unwrap();
+        game.undo_turn().unwrap();
+        assert_eq!(game.board(), &start);
+        assert!(!game.can_undo_turn());
+        assert!(game.undo().is_err());
+        game.redo_turn().unwrap();
+        assert_eq!(game.board(), &before_capture);
+
+        // Between two humans, the player who just moved can take back only
+        // that move
+        assert!(game.can_take_back());
+        assert_eq!(game.undo().unwrap().notation(), "f6-e5");
+        assert_eq!(game.get_current_player(), Player::White);
+        assert_eq!(game.moves().len(), 1);
+        game.redo().unwrap();
+
+        // Playing a new move forgets the moves which were taken back
+        game.undo_turn().unwrap();
+        play(&mut game, &["a3-b4"]);
+        assert!(game.redo().is_err());
+        assert_eq!(game.moves().len(), 1);
     }
 
+    #[test]
+    fn test_takeback_request() {
+        let mut game = Game::with_variant(Variant::English, GameMode::HumanVsHuman);
+        let start = *game.board();
+        let mover = game.get_current_player();
+        let this_move = game.board().parse_move(mover, "c3-d4").unwrap();
+        // The other player declines the first request and accepts the second
+        let mut answers = [false, true].into_iter();
+        let mut questions = Vec::new();
+        let mut ask = |question: &str| {
+            questions.push(question.to_string());
+            Ok(answers.next().unwrap())
+        };
+
+        // The player who just moved asks, and the other player decides
+        game.handle_action(mover, Action::Move(this_move), &mut ask).unwrap();
+        let after_move = *game.board();
+        game.handle_action(mover, Action::TakeBack, &mut ask).unwrap();
+        assert_eq!(game.board(), &after_move);
+        game.handle_action(mover, Action::TakeBack, &mut ask).unwrap();
+        assert_eq!(game.board(), &start);
+        assert_eq!(game.get_current_player(), mover);
+        assert!(game.moves().is_empty());
+
+        // The player to move can't take back their opponents move
+        play(&mut game, &["c3-d4"]);
+        game.handle_action(!mover, Action::TakeBack, &mut ask).unwrap();
+        assert_eq!(game.board(), &after_move);
+        let question = "White, allow Black to take back their last move?";
+        assert_eq!(questions, [question, question]);
+
+        // Nor can a player against the AI
+        let mode = GameMode::HumanVsAi(AiSettings::default());
+        let mut ai_game = Game::with_variant(Variant::English, mode);
+        play(&mut ai_game, &["c3-d4"]);
+        assert!(!ai_game.can_take_back());
+        let mut ask = |_: &str| -> anyhow::Result<bool> { panic!("Nobody to ask") };
+        ai_game.handle_action(mover, Action::TakeBack, &mut ask).unwrap();
+        assert_eq!(ai_game.moves().len(), 1);
+    }
 
+    #[test]
+    fn test_undo_setup() {
+        // The moves of a ballot are part of the opening, and can't be taken back
+        let ballot = Deck::ThreeMove
+            .ballots()
+            .iter()
+            .find(|ballot| {
+                let moves: Vec<String> = ballot
+                    .moves()
+                    .iter()
+                    .map(|m| m.display(Notation::Numeric, Variant::English).to_string())
+                    .collect();
+                moves == ["11-15", "23-19", "8-11"]
+            })
+            .unwrap();
+        let mut game = Game::with_ballot(ballot, GameMode::HumanVsHuman);
+        assert!(!game.can_take_back());
+        assert!(game.undo().is_err());
+
+        // The moves of a game record can be
+        let pdn: PdnGame = "1. 11-15 23-19 2. 8-11 *".parse().unwrap();
+        let mut game = Game::from_pdn(&pdn, GameMode::HumanVsHuman).unwrap();
+        assert!(game.can_undo_turn());
+        game.undo_turn().unwrap();
+        assert_eq!(game.moves().len(), 1);
+        assert_eq!(game.get_current_player(), Player::White);
+    }
+
+    #[test]
+    fn test_game_over() {
+        let game = Game::with_variant(Variant::English, GameMode::HumanVsHuman);
+        assert_eq!(game.game_over(), None);
+
+        // The player with peices left is the winner
+        let game = Game::from_fen(Variant::English, "W:W21:B1,2", GameMode::HumanVsHuman).unwrap();
+        assert_eq!(game.game_over(), None);
+        let game = Game::from_fen(Variant::English, "W:W21:B", GameMode::HumanVsHuman).unwrap();
+        assert_eq!(
+            game.game_over().and_then(|result| result.winner()),
+            Some(Player::White)
+        );
+        let mut game =
+            Game::from_fen(Variant::English, "B:W18:B14", GameMode::HumanVsHuman).unwrap();
+        play(&mut game, &["14x23"]);
+        let result = GameResult::Win {
+            winner: Player::Black,
+            reason: WinReason::NoPeices,
+        };
+        assert_eq!(game.game_over(), Some(result));
+
+        let mut game = Game::with_variant(Variant::English, GameMode::HumanVsHuman);
+        game.finish(GameResult::Win {
+            winner: Player::White,
+            reason: WinReason::Resignation,
+        });
+        assert_eq!(game.to_pdn().result, PdnResult::SecondPlayerWins);
+    }
+
+    #[test]
+    fn test_draw_by_repetition() {
+        let mode = GameMode::HumanVsHuman;
+        let mut game = Game::from_fen(Variant::English, "B:WK29,K30:BK1", mode).unwrap();
+        let shuffle = ["g1-h2", "h8-g7", "h2-g1", "g7-h8"];
+        play(&mut game, &shuffle);
+        play(&mut game, &shuffle[..3]);
+        assert_eq!(game.game_over(), None);
+
+        // The third time the kings are back where they started
+        play(&mut game, &shuffle[3..]);
+        let draw = GameResult::Draw(DrawReason::Repetition);
+        assert_eq!(game.game_over(), Some(draw));
+        assert_eq!(game.run().unwrap(), Some(draw));
+        assert_eq!(game.to_pdn().result, PdnResult::Draw);
+
+        // Taking a move back undoes the draw
+        game.undo().unwrap();
+        assert_eq!(game.game_over(), None);
+        assert_eq!(game.result(), None);
+    }
+
+    #[test]
+    fn test_move_limit() {
+        let mode = GameMode::HumanVsHuman;
+        let mut game = Game::from_fen(Variant::English, "B:WK29,K30:BK1", mode).unwrap();
+        assert_eq!(game.move_limit(), 40);
+        game.set_move_limit(2);
+        play(&mut game, &["g1-h2", "h8-g7", "h2-g3"]);
+        assert_eq!(game.game_over(), None);
+
+        // Each player has made two quiet moves
+        play(&mut game, &["f8-e7"]);
+        let draw = GameResult::Draw(DrawReason::MoveLimit);
+        assert_eq!(game.game_over(), Some(draw));
+    }
+
+    /// A board with the given men on it, and nothing else
+    fn board_with(black: &[&str], white: &[&str]) -> Board {
+        let mut board = Board::empty();
+        for (player, men) in [(Player::Black, black), (Player::White, white)] {
+            for pos in men {
+                board[Position::from_str(*pos).unwrap()].take_ownership(player);
+            }
+        }
+        board
+    }
+
+    #[test]
+    fn test_blocked() {
+        let blocked = GameResult::Win {
+            winner: Player::White,
+            reason: WinReason::NoLegalMoves,
+        };
+        let ai = AiSettings {
+            difficulty: Difficulty::Hard,
+            ..AiSettings::default()
+        };
+
+        // Black's only man can neither move nor capture, in any game mode
+        let board = board_with(&["a1"], &["b2", "c3"]);
+        for mode in [
+            GameMode::HumanVsHuman,
+            GameMode::HumanVsAi(ai),
+            GameMode::AiVsAi(ai, ai),
+        ] {
+            let mut game = Game::from_position(board, Player::Black, mode);
+            assert_eq!(game.game_over(), Some(blocked));
+            assert_eq!(game.run().unwrap(), Some(blocked));
+            assert_eq!(game.result(), Some(blocked));
+            assert_eq!(game.to_pdn().result, PdnResult::SecondPlayerWins);
+        }
+
+        // Men can be blocked in by their own side too
+        let board = board_with(&["a1", "b2"], &["a3", "c3", "d4"]);
+        let game = Game::from_position(board, Player::Black, GameMode::HumanVsHuman);
+        assert_eq!(game.game_over(), Some(blocked));
+        let board = board_with(&["a1", "b2"], &["a3", "c3"]);
+        let game = Game::from_position(board, Player::Black, GameMode::HumanVsHuman);
+        assert_eq!(game.game_over(), None);
+        let board = board_with(&["c1", "b2"], &["a3"]);
+        let game = Game::from_position(board, Player::White, GameMode::HumanVsHuman);
+        let result = game.game_over().unwrap();
+        assert_eq!(result.winner(), Some(Player::Black));
+
+        // The AI blocks its opponent in to win
+        let board = board_with(&["a1", "c1"], &["a3"]);
+        let mut game = Game::from_position(board, Player::Black, GameMode::AiVsAi(ai, ai));
+        let result = game.run().unwrap().unwrap();
+        assert_eq!(game.moves().len(), 1);
+        assert_eq!(result.to_string(), "Black wins, White has no legal moves");
+    }
+
+    #[test]
+    fn test_time_forfeit() {
+        let ai = AiSettings::default();
+        let mut game = Game::with_variant(Variant::English, GameMode::AiVsAi(ai, ai));
+        let time = Duration::from_nanos(1);
+        game.set_clock(Clock::with_remaining(time, Duration::from_secs(60), Duration::ZERO));
+
+        // The move found after the clock ran out is never played
+        let result = game.run().unwrap().unwrap();
+        assert!(game.moves().is_empty());
+        assert_eq!(result.to_string(), "White wins, Black ran out of time");
+        assert_eq!(game.to_pdn().result, PdnResult::SecondPlayerWins);
+    }
 }
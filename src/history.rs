@@ -0,0 +1,174 @@
+//! The positions reached during a game, used to draw games which go round in
+//! circles. Captures and man moves can never be undone, so only the positions
+//! since the last of them can ever come up again.
+
+use crate::{
+    board::Board, player::Player, r#move::Move, result::DrawReason, rules::RuleSet, tile::TileKind,
+    zobrist,
+};
+
+/// The key of a position, which includes the player to move
+pub fn key(board: &Board, player: Player) -> u64 {
+    board.hash() ^ zobrist::side_key(player)
+}
+
+/// Whether a move could be undone by a later one, which is only true of king
+/// moves that don't capture anything
+pub fn is_quiet(board: &Board, this_move: &Move) -> bool {
+    !this_move.is_capture() && board[this_move.from()].kind() == TileKind::King
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PositionHistory {
+    /// The key of every position in the game, oldest first
+    keys: Vec<u64>,
+    /// The number of quiet moves played before each position, counting back
+    /// to the last capture or man move
+    quiet_moves: Vec<usize>,
+}
+
+impl PositionHistory {
+    /// Start a history from the position a game begins in
+    pub fn new(board: &Board, player: Player) -> Self {
+        Self {
+            keys: vec![key(board, player)],
+            quiet_moves: vec![0],
+        }
+    }
+
+    /// Record `player` playing `this_move` from `board`
+    pub fn push(&mut self, board: &Board, player: Player, this_move: &Move) {
+        let mut after = *board;
+        after.apply_move(player, this_move);
+        self.push_position(&after, !player, is_quiet(board, this_move));
+    }
+
+    /// Record the position reached by a move, with `player` to move next
+    pub fn push_position(&mut self, board: &Board, player: Player, quiet: bool) {
+        let quiet_moves = if quiet { self.quiet_moves() + 1 } else { 0 };
+        self.keys.push(key(board, player));
+        self.quiet_moves.push(quiet_moves);
+    }
+
+    /// Forget the last position, going back to the one before it. The
+    /// position the game began in is always kept.
+    pub fn pop(&mut self) {
+        if self.keys.len() > 1 {
+            self.keys.pop();
+            self.quiet_moves.pop();
+        }
+    }
+
+    /// The key of the current position
+    pub fn current(&self) -> u64 {
+        // SAFETY: the starting position is never removed
+        *self.keys.last().unwrap()
+    }
+
+    /// The number of moves played since the last capture or man move
+    pub fn quiet_moves(&self) -> usize {
+        // SAFETY: the starting position is never removed
+        *self.quiet_moves.last().unwrap()
+    }
+
+    /// The number of times the current position has come up, including now
+    pub fn repetitions(&self) -> usize {
+        let current = self.current();
+        let since = self.keys.len() - 1 - self.quiet_moves();
+        self.keys[since..]
+            .iter()
+            .filter(|key| **key == current)
+            .count()
+    }
+
+    /// The number of moves since the current position last came up, or `None`
+    /// if this is the first time
+    pub fn last_repetition(&self) -> Option<usize> {
+        let current = self.current();
+        let since = self.keys.len() - 1 - self.quiet_moves();
+        self.keys[since..self.keys.len() - 1]
+            .iter()
+            .rev()
+            .position(|key| *key == current)
+            .map(|idx| idx + 1)
+    }
+
+    /// Whether the game is drawn in the current position, when each player
+    /// may make `move_limit` quiet moves, usually `rules.draw_move_limit()`
+    pub fn draw(&self, rules: &dyn RuleSet, move_limit: usize) -> Option<DrawReason> {
+        if self.repetitions() >= rules.repetition_limit() {
+            Some(DrawReason::Repetition)
+        } else if self.quiet_moves() >= 2 * move_limit {
+            // The limit is on the moves made by each player
+            Some(DrawReason::MoveLimit)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{board::Board, result::DrawReason, rules::Variant};
+
+    use super::PositionHistory;
+
+    #[test]
+    fn test_repetition() {
+        let (mut board, mut player) = Board::from_fen(Variant::English, "B:WK29,K30:BK1").unwrap();
+        let rules = board.rules();
+        let mut history = PositionHistory::new(&board, player);
+
+        // Both kings step away and back again, twice
+        for (round, text) in ["g1-h2", "h8-g7", "h2-g1", "g7-h8"]
+            .repeat(2)
+            .iter()
+            .enumerate()
+        {
+            assert_eq!(history.draw(rules, rules.draw_move_limit()), None);
+            assert_eq!(history.last_repetition().is_some(), round >= 4);
+            let this_move = board.parse_move(player, text).unwrap();
+            history.push(&board, player, &this_move);
+            board.apply_move(player, &this_move);
+            player = !player;
+            assert_eq!(history.quiet_moves(), round + 1);
+        }
+        assert_eq!(history.repetitions(), 3);
+        assert_eq!(history.last_repetition(), Some(4));
+        assert_eq!(
+            history.draw(rules, rules.draw_move_limit()),
+            Some(DrawReason::Repetition)
+        );
+
+        history.pop();
+        assert_eq!(history.repetitions(), 2);
+        assert_eq!(history.draw(rules, rules.draw_move_limit()), None);
+    }
+
+    #[test]
+    fn test_move_limit() {
+        let limit = 2 * Variant::Russian.rules().draw_move_limit();
+        let (board, player) = Board::from_fen(Variant::Russian, "W:W5:BK32").unwrap();
+        let rules = board.rules();
+        let mut history = PositionHistory::new(&board, player);
+
+        // Quiet moves are counted for both players
+        for square in 1..=limit {
+            assert_eq!(history.draw(rules, rules.draw_move_limit()), None);
+            let fen = format!("W:WK{square}:BK32");
+            let (board, player) = Board::from_fen(Variant::Russian, &fen).unwrap();
+            history.push_position(&board, player, true);
+        }
+        assert_eq!(history.quiet_moves(), limit);
+        assert_eq!(
+            history.draw(rules, rules.draw_move_limit()),
+            Some(DrawReason::MoveLimit)
+        );
+        assert_eq!(history.draw(rules, limit), None);
+
+        // A capture or man move starts the count again
+        history.push_position(&board, player, false);
+        assert_eq!(history.quiet_moves(), 0);
+        assert_eq!(history.draw(rules, rules.draw_move_limit()), None);
+    }
+}
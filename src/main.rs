@@ -1,28 +1,445 @@
-#![allow(dead_code, unused_imports, unused_variables)]
+#![allow(dead_code)]
 
-use anyhow::Result;
+use std::{
+    env,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Context, Result};
 
 use clearscreen::clear;
+use rand::seq::SliceRandom;
 
 use crate::{
-    board::{Board, BOARD_SIZE},
+    ballot::{pair_colours, Deck},
+    board::Board,
+    book::OpeningBook,
+    difficulty::AiSettings,
     game::{Game, GameMode},
-    player::Player,
-    r#move::{Move, Position},
-    tile::{Tile, TileKind},
+    pdn::PdnGame,
+    tablebase::{Outcome, Tablebase},
 };
 
+mod ballot;
+mod bitboard;
 mod board;
+mod book;
+mod clock;
+mod difficulty;
+mod eval;
 mod game;
+mod history;
 mod king_moves;
+mod mcts;
 mod r#move;
+mod pdn;
+mod perft;
 mod player;
+mod result;
+mod rules;
+mod save;
+mod search;
+mod tablebase;
 mod tile;
+mod transposition;
+mod zobrist;
 
 fn main() -> Result<()> {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    match args.first().map(String::as_str) {
+        Some("perft") => return run_perft(&args[1..]),
+        Some("tablebase") => return run_tablebase(&args[1..]),
+        Some("book") => return run_book(&args[1..]),
+        Some("analyse") => return run_analyse(&args[1..]),
+        Some("match") => return run_match(&args[1..]),
+        _ => (),
+    }
+
+    // `checkers resume <file> [options]` carries on a saved game
+    let (saved, args) = match args.first().map(String::as_str) {
+        Some("resume") => {
+            let path = args.get(1).context("Usage: checkers resume <file>")?;
+            (Some(Game::resume(path)?), &args[2..])
+        }
+        _ => (None, &args[..]),
+    };
+
+    let mut weights = None;
+    let mut threads = None;
+    let mut tablebase = None;
+    let mut book = None;
+    let mut ballot = None;
+    let mut record = None;
+    let mut pdn = None;
+    let mut notation = None;
+    let mut variant = None;
+    let mut fen = None;
+    let mut clock = None;
+    let mut move_limit = None;
+    let mut options = args.iter().peekable();
+    while let Some(option) = options.next() {
+        match option.as_str() {
+            "--weights" => {
+                weights = Some(options.next().context("Usage: checkers --weights <file>")?);
+            }
+            "--threads" => {
+                let count = options.next().context("Usage: checkers --threads <count>")?;
+                threads = Some(count.parse().context("Thread count must be a number")?);
+            }
+            "--tablebase" => {
+                tablebase = Some(options.next().context("Usage: checkers --tablebase <file>")?);
+            }
+            "--book" => {
+                book = Some(options.next().context("Usage: checkers --book <file>")?);
+            }
+            "--ballot" => {
+                let usage = "Usage: checkers --ballot <deck>[:<number>]";
+                let text = options.next().context(usage)?;
+                let (deck, number) = match text.split_once(':') {
+                    Some((deck, number)) => (deck, Some(number)),
+                    None => (text.as_str(), None),
+                };
+                let deck: Deck = deck.parse()?;
+                ballot = Some(match number {
+                    Some(number) => deck.ballot(number.parse().context(usage)?)?,
+                    None => deck.draw(&mut rand::thread_rng()),
+                });
+            }
+            "--pdn" => {
+                let usage = "Usage: checkers --pdn <file> [game number]";
+                let path = options.next().context(usage)?;
+                let number = match options.next_if(|arg| !arg.starts_with("--")) {
+                    Some(number) => Some(number.parse().context("Game number must be a number")?),
+                    None => None,
+                };
+                pdn = Some(PdnGame::load(path, number)?);
+            }
+            "--record" => {
+                record = Some(options.next().context("Usage: checkers --record <file>")?);
+            }
+            "--notation" => {
+                let text = options
+                    .next()
+                    .context("Usage: checkers --notation <algebraic|numeric>")?;
+                notation = Some(text.parse()?);
+            }
+            "--variant" => {
+                let name = options.next().context("Usage: checkers --variant <name>")?;
+                variant = Some(name.parse()?);
+            }
+            "--clock" => {
+                let usage = "Usage: checkers --clock <minutes>[+<increment seconds>]";
+                clock = Some(options.next().context(usage)?.parse()?);
+            }
+            "--move-limit" => {
+                let usage = "Usage: checkers --move-limit <moves>";
+                let limit = options.next().context(usage)?;
+                move_limit = Some(limit.parse().context("Move limit must be a number")?);
+            }
+            "--fen" => {
+                fen = Some(options.next().context("Usage: checkers --fen <position>")?);
+            }
+            command => return Err(anyhow!("Unknown command {command}")),
+        }
+    }
+
+    // Each of these chooses the position the game starts from, so only one
+    // of them can be used, and it decides the variant
+    let starts = [saved.is_some(), ballot.is_some(), pdn.is_some(), fen.is_some()];
+    if starts.into_iter().filter(|given| *given).count() > 1 {
+        return Err(anyhow!("Only one of resume, --ballot, --pdn and --fen can be used"));
+    }
+    let known_variant = match (&saved, &ballot, &pdn) {
+        (Some(game), _, _) => Some(("The saved game", game.board().variant())),
+        (_, Some(ballot), _) => Some(("Ballots", ballot.board().variant())),
+        (_, _, Some(pdn)) => Some(("The PDN game", pdn.variant()?)),
+        _ => None,
+    };
+    if let (Some(variant), Some((game, known))) = (variant, known_variant) {
+        if variant != known {
+            return Err(anyhow!("{game} can only be played as {known}, not {variant}"));
+        }
+    }
+
     clear()?;
-    let mut game = Game::new();
-    let winner = game.run()?;
-    println!("WINNER: {:?}", winner);
+    if fen.is_some() && variant.is_none() {
+        println!("Playing the position as English draughts, choose another with --variant");
+    }
+    let mut game = if let Some(game) = saved {
+        game
+    } else if let Some(ballot) = ballot {
+        println!("Playing {ballot}");
+        Game::with_ballot(ballot, GameMode::select_gamemode()?)
+    } else if let Some(pdn) = pdn {
+        Game::from_pdn(&pdn, GameMode::select_gamemode()?)?
+    } else if let Some(fen) = fen {
+        Game::from_fen(variant.unwrap_or_default(), fen, GameMode::select_gamemode()?)?
+    } else if let Some(variant) = variant {
+        Game::with_variant(variant, GameMode::select_gamemode()?)
+    } else {
+        Game::new()
+    };
+    if let Some(path) = weights {
+        game.load_weights(path)?;
+    }
+    if let Some(threads) = threads {
+        game.search_config_mut().threads = threads;
+    }
+    if let Some(path) = tablebase {
+        game.load_tablebase(path)?;
+    }
+    if let Some(path) = book {
+        game.load_book(path)?;
+    }
+    if let Some(notation) = notation {
+        game.set_notation(notation);
+    }
+    if let Some(clock) = clock {
+        game.set_clock(clock);
+    }
+    if let Some(move_limit) = move_limit {
+        game.set_move_limit(move_limit);
+    }
+    let result = game.run();
+    if let Some(path) = record {
+        game.to_pdn().save(path)?;
+    }
+    if let Some(result) = result? {
+        println!("{result}");
+    }
+    Ok(())
+}
+
+/// `checkers perft <depth> [moves] [--variant <name>] [--fen <position>]`,
+/// where the moves are played from the start of the game, or from the
+/// PDN-FEN position if one is given, e.g. "c3-d4 f6-e5"
+fn run_perft(args: &[String]) -> Result<()> {
+    let usage = "Usage: checkers perft <depth> [moves] [--variant <name>] [--fen <position>]";
+    let mut variant = rules::Variant::default();
+    let mut fen = None;
+    let mut positional = Vec::new();
+    let mut options = args.iter();
+    while let Some(arg) = options.next() {
+        match arg.as_str() {
+            "--variant" => variant = options.next().context(usage)?.parse()?,
+            "--fen" => fen = Some(options.next().context(usage)?),
+            _ => positional.push(arg),
+        }
+    }
+
+    let depth: usize = positional
+        .first()
+        .context(usage)?
+        .parse()
+        .context("Depth must be a number")?;
+
+    let (mut board, mut player) = match fen {
+        Some(fen) => Board::from_fen(variant, fen)?,
+        None => {
+            let board = Board::with_variant(variant);
+            (board, board.rules().first_player())
+        }
+    };
+    for text in positional.get(1).map(|moves| moves.split_whitespace()).into_iter().flatten() {
+        let this_move = board.parse_move(player, text)?;
+        board.make_move_for(player, this_move)?;
+        player = !player;
+    }
+
+    println!("{board}");
+    let start = Instant::now();
+    let mut total = 0;
+    for (root_move, nodes) in perft::divide(&board, player, depth) {
+        println!("{root_move}: {nodes}");
+        total += nodes;
+    }
+    // Nothing is divided at depth 0, where the board is the only node
+    if depth == 0 {
+        total = perft::perft(&board, player, depth);
+    }
+
+    let elapsed = start.elapsed();
+    println!();
+    println!("Nodes: {total}");
+    println!(
+        "Time: {:.3}s ({:.0} nodes/s)",
+        elapsed.as_secs_f64(),
+        total as f64 / elapsed.as_secs_f64()
+    );
+    Ok(())
+}
+
+/// `checkers tablebase <peices> <file>`, generate endgame tables for every
+/// position with up to `peices` peices
+fn run_tablebase(args: &[String]) -> Result<()> {
+    let usage = "Usage: checkers tablebase <peices> <file>";
+    let peices: usize = args
+        .first()
+        .context(usage)?
+        .parse()
+        .context("Peices must be a number")?;
+    let path = args.get(1).context(usage)?;
+
+    let start = Instant::now();
+    let tablebase = Tablebase::generate(peices, |tablebase, peices| {
+        let (mut wins, mut losses, mut draws) = (0, 0, 0);
+        for outcome in tablebase.outcomes(peices) {
+            match outcome {
+                Outcome::Win(_) => wins += 1,
+                Outcome::Loss(_) => losses += 1,
+                Outcome::Draw => draws += 1,
+            }
+        }
+        println!(
+            "{peices} peices: {} positions, {wins} wins, {losses} losses, {draws} draws ({:.1}s)",
+            wins + losses + draws,
+            start.elapsed().as_secs_f64()
+        );
+    })?;
+
+    tablebase.save(path)?;
+    println!("Saved to {path}");
+    Ok(())
+}
+
+/// `checkers book <games> <file> [plies] [variant]`, build an opening book
+/// from a file of game records, one game per line
+fn run_book(args: &[String]) -> Result<()> {
+    let usage = "Usage: checkers book <games> <file> [plies] [variant]";
+    let games_path = args.first().context(usage)?;
+    let path = args.get(1).context(usage)?;
+    let plies = match args.get(2) {
+        Some(plies) => plies.parse().context("Plies must be a number")?,
+        None => book::DEFAULT_PLIES,
+    };
+    let variant = match args.get(3) {
+        Some(variant) => variant.parse()?,
+        None => rules::Variant::default(),
+    };
+
+    let games = std::fs::read_to_string(games_path)
+        .with_context(|| format!("Failed to read games from {games_path}"))?;
+    let book = OpeningBook::from_games(variant, &games, plies)?;
+    book.save(path)?;
+    println!("Saved {} positions to {path}", book.len());
+    Ok(())
+}
+
+/// `checkers analyse <position> [variant] [--depth <moves>] [--time <seconds>]
+/// [--threads <count>] [--weights <file>]`, search a position given in
+/// PDN-FEN and print the best move
+fn run_analyse(args: &[String]) -> Result<()> {
+    let usage = "Usage: checkers analyse <position> [variant] [--depth <moves>] \
+                 [--time <seconds>] [--threads <count>] [--weights <file>]";
+    let mut config = search::SearchConfig::default();
+    let mut depth = None;
+    let mut time = None;
+    let mut threads = None;
+    let mut positional = Vec::new();
+    let mut options = args.iter();
+    while let Some(arg) = options.next() {
+        match arg.as_str() {
+            "--depth" => {
+                let text = options.next().context(usage)?;
+                depth = Some(text.parse().context("Depth must be a number")?);
+            }
+            "--time" => {
+                let text = options.next().context(usage)?;
+                let seconds: f64 = text.parse().context("Time must be a number of seconds")?;
+                let time_limit = Duration::try_from_secs_f64(seconds)
+                    .map_err(|_| anyhow!("Invalid time {text}"))?;
+                time = Some(search::TimeControl::PerMove(time_limit));
+            }
+            "--threads" => {
+                let text = options.next().context(usage)?;
+                threads = Some(text.parse().context("Thread count must be a number")?);
+            }
+            "--weights" => {
+                config.weights = eval::EvalWeights::load(options.next().context(usage)?)?;
+            }
+            _ => positional.push(arg),
+        }
+    }
+
+    let fen = positional.first().context(usage)?;
+    let variant = match positional.get(1) {
+        Some(variant) => variant.parse()?,
+        None => rules::Variant::default(),
+    };
+    // A fixed depth is searched without a time limit on one thread, unless
+    // told otherwise, so that it gives the same result every time
+    if let Some(depth) = depth {
+        config = search::SearchConfig {
+            weights: config.weights,
+            ..search::SearchConfig::fixed_depth(depth)
+        };
+    }
+    if let Some(time) = time {
+        config.time = time;
+    }
+    if let Some(threads) = threads {
+        config.threads = threads;
+    }
+
+    let (board, player) = Board::from_fen(variant, fen)?;
+    println!("{board}");
+    println!("{player} to move");
+
+    let result = search::Searcher::new(config).search(&board, player);
+    let Some(best_move) = result.best_move else {
+        println!("{player} has no legal moves");
+        return Ok(());
+    };
+    let line: Vec<String> = result
+        .principal_variation
+        .iter()
+        .map(|m| m.display(r#move::Notation::Numeric, variant).to_string())
+        .collect();
+    println!(
+        "Best move: {} (score {}, depth {}, {} nodes)",
+        best_move.display(r#move::Notation::Numeric, variant),
+        result.score,
+        result.depth,
+        result.nodes
+    );
+    println!("Expected line: {}", line.join(" "));
+    Ok(())
+}
+
+/// `checkers match <deck> [ballots]`, play a match between two AIs. Each
+/// ballot drawn is played twice, with the AIs swapping colours.
+fn run_match(args: &[String]) -> Result<()> {
+    let usage = "Usage: checkers match <deck> [ballots]";
+    let deck: Deck = args.first().context(usage)?.parse()?;
+    let count = match args.get(1) {
+        Some(count) => count.parse().context("Ballots must be a number")?,
+        None => 1,
+    };
+
+    let first = AiSettings::select("first AI")?;
+    let second = AiSettings::select("second AI")?;
+    let ballots: Vec<_> = deck
+        .ballots()
+        .choose_multiple(&mut rand::thread_rng(), count)
+        .collect();
+
+    let mut score = 0.0;
+    let pairings = pair_colours(&ballots);
+    for (idx, pairing) in pairings.iter().enumerate() {
+        let mut game = Game::with_ballot(pairing.ballot, pairing.mode(first, second));
+        // Neither side is a human, so the game can't be saved part way through
+        let result = game.run()?.context("The match game ended without a result")?;
+        score += pairing.score(&result);
+        println!(
+            "Game {} of {}, {}: {result}",
+            idx + 1,
+            pairings.len(),
+            pairing.ballot
+        );
+    }
+
+    let total = pairings.len() as f64;
+    println!("First AI ({first}) {score} - {} Second AI ({second})", total - score);
     Ok(())
-}
\ No newline at end of file
+}
@@ -0,0 +1,324 @@
+//! Monte Carlo tree search, an alternative to the alpha-beta search which
+//! judges moves by playing out many games from them rather than by evaluating
+//! positions. Each iteration selects a promising line with UCT, expands one
+//! new move, plays the game out to the end and passes the result back up the
+//! tree. Playouts don't keep track of the positions they pass through, so
+//! draws by repetition or the move limit are never scored. A playout which
+//! goes on too long is judged by the evaluation instead.
+
+use std::time::{Duration, Instant};
+
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use slab_tree::{NodeId, Tree, TreeBuilder};
+
+use crate::{
+    board::Board,
+    eval::{self, EvalWeights},
+    player::Player,
+    r#move::Move,
+};
+
+/// When the search stops
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MctsLimit {
+    Iterations(usize),
+    Time(Duration),
+}
+
+/// How moves are chosen when playing out a game
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Playout {
+    /// Every legal move is equally likely
+    Random,
+    /// Half of the moves are chosen by the evaluation function, which makes
+    /// playouts more like real games at the cost of fewer iterations
+    Heuristic,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct MctsConfig {
+    pub limit: MctsLimit,
+    /// How much UCT favours moves which haven't been tried much over moves
+    /// which have done well so far
+    pub exploration: f64,
+    pub playout: Playout,
+    /// Playouts longer than this are stopped and judged by the evaluation
+    pub max_playout_moves: usize,
+    pub weights: EvalWeights,
+    /// Seed for the random number generator, so searches can be repeated
+    pub seed: Option<u64>,
+}
+
+impl Default for MctsConfig {
+    fn default() -> Self {
+        Self {
+            limit: MctsLimit::Time(Duration::from_secs(1)),
+            exploration: std::f64::consts::SQRT_2,
+            playout: Playout::Random,
+            max_playout_moves: 150,
+            weights: EvalWeights::default(),
+            seed: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MctsResult {
+    /// The most visited move, or `None` if the player has no legal moves
+    pub best_move: Option<Move>,
+    /// The share of playouts through the best move which were won
+    pub win_rate: f64,
+    pub iterations: usize,
+}
+
+/// A position in the search tree
+struct MctsNode {
+    board: Board,
+    /// The player to move
+    player: Player,
+    /// The move which led to this position
+    last_move: Option<Move>,
+    /// Legal moves which don't have a child node yet
+    untried: Vec<Move>,
+    visits: u32,
+    /// Playouts won by the player who made `last_move`, with draws counting
+    /// as half a win
+    wins: f64,
+}
+
+impl MctsNode {
+    fn new(board: Board, player: Player, last_move: Option<Move>) -> Self {
+        Self {
+            untried: board.get_legal_moves(player),
+            board,
+            player,
+            last_move,
+            visits: 0,
+            wins: 0.0,
+        }
+    }
+}
+
+pub struct Mcts {
+    config: MctsConfig,
+    rng: StdRng,
+}
+
+impl Mcts {
+    pub fn new(config: MctsConfig) -> Self {
+        let rng = match config.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+
+        Self { config, rng }
+    }
+
+    pub fn search(&mut self, board: &Board, player: Player) -> MctsResult {
+        let mut tree = TreeBuilder::new()
+            .with_root(MctsNode::new(*board, player, None))
+            .build();
+        let root_id = tree.root_id().unwrap();
+
+        let start = Instant::now();
+        let mut iterations = 0;
+        while !self.finished(iterations, start) {
+            let leaf_id = self.select(&tree, root_id);
+            let leaf_id = self.expand(&mut tree, leaf_id);
+            let winner = self.playout(tree.get(leaf_id).unwrap().data());
+            backpropagate(&mut tree, leaf_id, winner);
+            iterations += 1;
+        }
+
+        // Take a move which wins on the spot, otherwise the move which was
+        // explored the most
+        let root = tree.root().unwrap();
+        let best = root
+            .children()
+            .max_by_key(|child| {
+                let game_over = child.data().untried.is_empty() && child.first_child().is_none();
+                (game_over, child.data().visits)
+            })
+            .map(|child| child.data());
+        MctsResult {
+            best_move: best.and_then(|child| child.last_move.clone()),
+            win_rate: best.map_or(0.0, |child| child.wins / f64::from(child.visits.max(1))),
+            iterations,
+        }
+    }
+
+    fn finished(&self, iterations: usize, start: Instant) -> bool {
+        match self.config.limit {
+            MctsLimit::Iterations(limit) => iterations >= limit,
+            // Always run at least one iteration so there is a move to play
+            MctsLimit::Time(budget) => iterations > 0 && start.elapsed() >= budget,
+        }
+    }
+
+    /// Walk down the tree through fully expanded nodes, taking the child with
+    /// the best UCT score each time
+    fn select(&self, tree: &Tree<MctsNode>, root_id: NodeId) -> NodeId {
+        let mut node = tree.get(root_id).unwrap();
+
+        while node.data().untried.is_empty() {
+            let parent_visits = f64::from(node.data().visits.max(1));
+            let best_child = node.children().max_by(|a, b| {
+                self.uct(a.data(), parent_visits)
+                    .total_cmp(&self.uct(b.data(), parent_visits))
+            });
+
+            match best_child {
+                Some(child) => node = child,
+                // The game is over in this position
+                None => break,
+            }
+        }
+
+        node.node_id()
+    }
+
+    fn uct(&self, node: &MctsNode, parent_visits: f64) -> f64 {
+        if node.visits == 0 {
+            return f64::INFINITY;
+        }
+
+        let visits = f64::from(node.visits);
+        node.wins / visits + self.config.exploration * (parent_visits.ln() / visits).sqrt()
+    }
+
+    /// Add a child for one of the nodes untried moves, returning the new
+    /// child, or the node itself if the game is over
+    fn expand(&mut self, tree: &mut Tree<MctsNode>, node_id: NodeId) -> NodeId {
+        let mut node = tree.get_mut(node_id).unwrap();
+        let data = node.data();
+        if data.untried.is_empty() {
+            return node_id;
+        }
+
+        let idx = self.rng.gen_range(0..data.untried.len());
+        let this_move = data.untried.swap_remove(idx);
+        let mut board = data.board;
+        let player = data.player;
+        board.apply_move(player, &this_move);
+
+        node.append(MctsNode::new(board, !player, Some(this_move)))
+            .node_id()
+    }
+
+    /// Play the game out from `node`, returning the winner, or `None` if it
+    /// was a draw
+    fn playout(&mut self, node: &MctsNode) -> Option<Player> {
+        let mut board = node.board;
+        let mut player = node.player;
+
+        for _ in 0..self.config.max_playout_moves {
+            let moves = board.get_legal_moves(player);
+            if moves.is_empty() {
+                return Some(!player);
+            }
+
+            let this_move = match self.config.playout {
+                // Avoid leaving a peice to be captured, then pick the move
+                // the evaluation likes best
+                Playout::Heuristic if self.rng.gen_bool(0.5) => moves.iter().max_by_key(|m| {
+                    let mut child = board;
+                    child.apply_move(player, m);
+                    (
+                        !child.has_capture(!player),
+                        -eval::evaluate(&child, !player, &self.config.weights),
+                    )
+                }),
+                _ => moves.choose(&mut self.rng),
+            }
+            .unwrap();
+
+            board.apply_move(player, this_move);
+            player = !player;
+        }
+
+        // Too long to play out, so give the win to whoever is clearly ahead
+        let score = eval::evaluate(&board, player, &self.config.weights);
+        if score > self.config.weights.man {
+            Some(player)
+        } else if score < -self.config.weights.man {
+            Some(!player)
+        } else {
+            None
+        }
+    }
+}
+
+/// Record the result of a playout in every node from `node_id` up to the root
+fn backpropagate(tree: &mut Tree<MctsNode>, node_id: NodeId, winner: Option<Player>) {
+    let mut next = Some(node_id);
+
+    while let Some(node_id) = next {
+        let mut node = tree.get_mut(node_id).unwrap();
+        let data = node.data();
+        data.visits += 1;
+        data.wins += match winner {
+            // Wins are counted for the player who moved into the node
+            Some(winner) if winner != data.player => 1.0,
+            Some(_) => 0.0,
+            None => 0.5,
+        };
+
+        next = node.parent().map(|parent| parent.node_id());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{board::Board, player::Player, r#move::Position};
+
+    use super::{Mcts, MctsConfig, MctsLimit, Playout};
+
+    fn config(iterations: usize) -> MctsConfig {
+        MctsConfig {
+            limit: MctsLimit::Iterations(iterations),
+            seed: Some(7),
+            ..MctsConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_finds_winning_move() {
+        let mut board = Board::empty();
+        let pos = |s: &str| Position::from_str(s).unwrap();
+
+        // Moving a5 to b6 leaves whites only man with nowhere to go
+        board[pos("a5")].take_ownership(Player::Black);
+        board[pos("c5")].take_ownership(Player::Black);
+        board[pos("a7")].take_ownership(Player::White);
+        let winning_move = board.parse_move(Player::Black, "a5-b6").unwrap();
+
+        for playout in [Playout::Random, Playout::Heuristic] {
+            let config = MctsConfig {
+                playout,
+                ..config(300)
+            };
+            let result = Mcts::new(config).search(&board, Player::Black);
+            assert_eq!(result.best_move.as_ref(), Some(&winning_move));
+            assert_eq!(result.win_rate, 1.0);
+            assert_eq!(result.iterations, 300);
+        }
+
+        // With no peices left there is nothing to play
+        let losing_move = board.parse_move(Player::Black, "c5-b6").unwrap();
+        board.apply_move(Player::Black, &losing_move);
+        let capture = board.parse_move(Player::White, "a7xc5").unwrap();
+        board.apply_move(Player::White, &capture);
+        board[pos("a5")].leave();
+        let result = Mcts::new(config(10)).search(&board, Player::Black);
+        assert_eq!(result.best_move, None);
+    }
+
+    #[test]
+    fn test_repeatable() {
+        let board = Board::new();
+        let first = Mcts::new(config(200)).search(&board, Player::Black);
+        let second = Mcts::new(config(200)).search(&board, Player::Black);
+        assert_eq!(first, second);
+        assert!(first.best_move.is_some());
+    }
+}
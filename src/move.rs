@@ -2,26 +2,42 @@ use anyhow::{anyhow, Result};
 use regex::Regex;
 
 use crate::board::*;
+use crate::rules::Variant;
 
 use std::fmt;
 
+/// A tile on the board, stored as its index along with the size of the board
+/// it belongs to
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct Position(usize);
+pub struct Position {
+    idx: usize,
+    size: usize,
+}
 
 impl Position {
     pub fn from_idx(idx: usize) -> Self {
-        if idx > (BOARD_SIZE * BOARD_SIZE) - 1 {
-            panic!("Position {} outside of board limits", Self(idx));
+        Self::from_idx_with_size(idx, BOARD_SIZE)
+    }
+
+    pub fn from_idx_with_size(idx: usize, size: usize) -> Self {
+        if idx > (size * size) - 1 {
+            panic!("Position {} outside of board limits", Self { idx, size });
         }
 
-        Self(idx)
+        Self { idx, size }
     }
 
     pub fn from_idx_checked(idx: usize) -> Result<Self> {
         if idx > BOARD_SIZE * BOARD_SIZE - 1 {
-            Err(anyhow!("Position {} outside of board limits", Self(idx)))
+            Err(anyhow!(
+                "Position {} outside of board limits",
+                Self {
+                    idx,
+                    size: BOARD_SIZE
+                }
+            ))
         } else {
-            Ok(Self(idx))
+            Ok(Self::from_idx(idx))
         }
     }
 
@@ -32,50 +48,99 @@ impl Position {
     }
 
     pub fn from_coords_checked(x: isize, y: isize) -> Result<Self> {
-        if x < 0 || y < 0 || x > BOARD_SIZE as isize || y > BOARD_SIZE as isize {
+        Self::from_coords_checked_with_size(x, y, BOARD_SIZE)
+    }
+
+    pub fn from_coords_checked_with_size(x: isize, y: isize, size: usize) -> Result<Self> {
+        if x < 0 || y < 0 || x >= size as isize || y >= size as isize {
             return Err(anyhow!("Index error"));
         }
-        let idx = Board::coords_to_idx(x as usize, y as usize);
 
-        Self::from_idx_checked(idx)
+        Ok(Self::from_idx_with_size(y as usize * size + x as usize, size))
     }
 
-    #[allow(clippy::unwrap_used)]
     pub fn from_str<S: Into<String>>(buffer: S) -> Result<Self> {
+        Self::from_str_with_size(buffer, BOARD_SIZE)
+    }
+
+    /// Parse an algebraic position such as `c3` on a board with `size` tiles
+    /// along each side
+    #[allow(clippy::unwrap_used)]
+    pub fn from_str_with_size<S: Into<String>>(buffer: S, size: usize) -> Result<Self> {
         let buffer: String = buffer.into();
-        let expected_pattern = Regex::new(r"(?m)[A-Ha-h][1-8]")?;
+        let expected_pattern = Regex::new(r"^([A-La-l])([1-9][0-9]?)$")?;
 
-        if expected_pattern.is_match(&buffer) {
-            // SAFETY: guarentted not to panic as the regex to enter this path
-            // requires length == 2
-            let x: char = buffer.chars().nth(0).unwrap();
-            let y: usize = buffer.chars().nth(1).unwrap() as usize - 48;
-            let x_range: Vec<char> = (97u8..97u8 + BOARD_SIZE as u8).map(|n| n as char).collect();
-            let y_range: Vec<usize> = (1..BOARD_SIZE + 1).collect();
+        if let Some(captures) = expected_pattern.captures(buffer.trim()) {
+            // SAFETY: guarenteed not to panic as both groups are required for
+            // the regex to match
+            let x = captures.get(1).unwrap().as_str().to_ascii_lowercase().as_bytes()[0] - b'a';
+            let y: usize = captures.get(2).unwrap().as_str().parse()?;
 
-            if !x_range.contains(&x) || !y_range.contains(&y) {
+            if x as usize >= size || y == 0 || y > size {
                 return Err(anyhow!("invalid location selected"));
             }
 
-            let pos = Position::from_idx(Board::coords_to_idx(x as usize - 97, y - 1));
-            Ok(pos)
+            Self::from_coords_checked_with_size(x as isize, y as isize - 1, size)
         } else {
             Err(anyhow!("Could not parse the string"))
         }
     }
 
     pub fn idx(&self) -> usize {
-        self.0
+        self.idx
+    }
+
+    /// The number of tiles along each side of the board this position is on
+    pub fn size(&self) -> usize {
+        self.size
     }
 
     pub fn coords(&self) -> (usize, usize) {
-        Board::idx_to_coords(self.0)
+        (self.idx % self.size, self.idx / self.size)
+    }
+
+    /// The number of this tile in numeric notation, or `None` if peices can't
+    /// be placed on it in `variant`. Playable tiles are numbered from 1, row
+    /// by row from black's back row, starting each row at the end furthest
+    /// from the a file. In English draughts black's men start on 1 to 12.
+    pub fn square_number(&self, variant: Variant) -> Option<usize> {
+        let rules = variant.rules();
+        let (x, y) = self.coords();
+        if self.size != rules.board_size() || !rules.is_playable(x, y) {
+            return None;
+        }
+
+        let before = (x + 1..self.size)
+            .filter(|x| rules.is_playable(*x, y))
+            .count();
+        Some(y * self.size / 2 + before + 1)
+    }
+
+    /// Get the tile with a given number in numeric notation for `variant`
+    pub fn from_square(square: usize, variant: Variant) -> Result<Self> {
+        let rules = variant.rules();
+        let size = rules.board_size();
+        let per_row = size / 2;
+        if square == 0 || square > size * per_row {
+            return Err(anyhow!(
+                "There is no square {square}, squares are numbered 1 to {}",
+                size * per_row
+            ));
+        }
+
+        let y = (square - 1) / per_row;
+        let x = (0..size)
+            .rev()
+            .filter(|x| rules.is_playable(*x, y))
+            .nth((square - 1) % per_row)
+            .ok_or_else(|| anyhow!("There is no square {square}"))?;
+        Ok(Self::from_idx_with_size(y * size + x, size))
     }
 }
 
 impl fmt::Display for Position {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let (x, y) = Board::idx_to_coords(self.0);
+        let (x, y) = self.coords();
 
         let x = (x as u8 + 97) as char;
         let y = y + 1;
@@ -86,7 +151,7 @@ impl fmt::Display for Position {
 
 impl fmt::Debug for Position {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let (x, y) = Board::idx_to_coords(self.0);
+        let (x, y) = self.coords();
 
         let x = (x as u8 + 97) as char;
         let y = y + 1;
@@ -95,27 +160,38 @@ impl fmt::Debug for Position {
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// A single turn, described by every tile the moving peice lands on (starting
+/// with the tile it left) and the tiles of any peices it captured on the way.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Move {
-    from: Position,
-    to: Position,
+    path: Vec<Position>,
+    captured: Vec<Position>,
 }
 
 impl Move {
     pub fn new(from: usize, to: usize) -> Self {
+        Self::from_positions(Position::from_idx(from), Position::from_idx(to))
+    }
+
+    pub fn from_positions(from: Position, to: Position) -> Self {
         Self {
-            from: Position(from),
-            to: Position(to),
+            path: vec![from, to],
+            captured: Vec::new(),
         }
     }
 
-    pub fn from_positions(from: Position, to: Position) -> Self {
-        Self { from, to }
+    /// Build a move from a full list of landing tiles and the peices jumped
+    /// between them. `path` must contain at least the starting tile and one
+    /// landing tile.
+    pub fn chain(path: Vec<Position>, captured: Vec<Position>) -> Self {
+        assert!(path.len() >= 2, "A move must contain at least two positions");
+        Self { path, captured }
     }
+
     /// Calculate the manhattan distance between two tiles on the board
     pub fn delta(&self) -> (isize, isize) {
-        let from = self.from.coords();
-        let to = self.to.coords();
+        let from = self.from().coords();
+        let to = self.to().coords();
         (
             to.0 as isize - from.0 as isize,
             to.1 as isize - from.1 as isize,
@@ -123,16 +199,120 @@ impl Move {
     }
 
     pub fn from(&self) -> Position {
-        self.from
+        self.path[0]
     }
 
+    #[allow(clippy::unwrap_used)]
     pub fn to(&self) -> Position {
-        self.to
+        // SAFETY: a move always has at least two positions in its path
+        *self.path.last().unwrap()
+    }
+
+    /// Every tile the peice occupies over the course of the move
+    pub fn path(&self) -> &[Position] {
+        &self.path
+    }
+
+    /// The tiles of every peice captured by this move
+    pub fn captured(&self) -> &[Position] {
+        &self.captured
+    }
+
+    pub fn is_capture(&self) -> bool {
+        !self.captured.is_empty()
+    }
+
+    /// Write the move as the tiles it lands on, joined by `x` for captures and
+    /// `-` otherwise, e.g. `c3-d4` or `a3xc5xe7`. This is the form read by
+    /// `Board::parse_move`.
+    pub fn notation(&self) -> String {
+        let separator = if self.is_capture() { "x" } else { "-" };
+        self.path
+            .iter()
+            .map(|pos| pos.to_string())
+            .collect::<Vec<_>>()
+            .join(separator)
+    }
+}
+
+/// How moves are written out
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Notation {
+    /// The tiles the peice lands on, e.g. `a3 -> b4`
+    #[default]
+    Algebraic,
+    /// Numbered squares as used by most checkers books, e.g. `11-15` or
+    /// `11x18x25`
+    Numeric,
+}
+
+impl fmt::Display for Notation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Notation::Algebraic => "algebraic",
+            Notation::Numeric => "numeric",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl std::str::FromStr for Notation {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "algebraic" => Ok(Notation::Algebraic),
+            "numeric" => Ok(Notation::Numeric),
+            _ => Err(anyhow!("Unknown notation {s}, expected algebraic or numeric")),
+        }
+    }
+}
+
+/// Writes a move in a chosen notation, see `Move::display`
+pub struct MoveDisplay<'a> {
+    this_move: &'a Move,
+    notation: Notation,
+    variant: Variant,
+}
+
+impl fmt::Display for MoveDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let squares: Option<Vec<String>> = self
+            .this_move
+            .path
+            .iter()
+            .map(|pos| pos.square_number(self.variant).map(|square| square.to_string()))
+            .collect();
+
+        match (self.notation, squares) {
+            (Notation::Numeric, Some(squares)) => {
+                let separator = if self.this_move.is_capture() { "x" } else { "-" };
+                write!(f, "{}", squares.join(separator))
+            }
+            // Moves which don't fit the variants board can only be written
+            // algebraically
+            _ => write!(f, "{}", self.this_move),
+        }
+    }
+}
+
+impl Move {
+    /// Write the move in `notation`, numbering squares for `variant`
+    pub fn display(&self, notation: Notation, variant: Variant) -> MoveDisplay<'_> {
+        MoveDisplay {
+            this_move: self,
+            notation,
+            variant,
+        }
     }
 }
 
 impl fmt::Display for Move {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{} -> {}", self.from, self.to)
+        write!(f, "{}", self.from())?;
+        for pos in &self.path[1..] {
+            write!(f, " -> {pos}")?;
+        }
+        Ok(())
     }
 }
@@ -0,0 +1,709 @@
+//! Portable Draughts Notation, the standard format for exchanging games with
+//! other draughts software. A game is a list of `[Name "value"]` tag pairs
+//! followed by the moves, written with numbered squares such as `11-15` or
+//! `22x15`. Comments go in `{braces}`, alternative lines in `(brackets)`, and
+//! the game ends with its result.
+//!
+//! Squares are numbered as described by `Position::square_number`, and the
+//! variant is given by the `GameType` tag, which defaults to English draughts.
+
+use std::{fmt, fs, path::Path, str::FromStr};
+
+use anyhow::{anyhow, bail, Context, Result};
+
+use crate::{
+    board::Board,
+    player::Player,
+    r#move::{Move, Position},
+    result::GameResult,
+    rules::Variant,
+};
+
+/// The number PDN uses for a variant in the `GameType` tag
+pub fn game_type(variant: Variant) -> usize {
+    match variant {
+        Variant::International => 20,
+        Variant::English => 21,
+        Variant::Italian => 22,
+        Variant::Pool => 23,
+        Variant::Russian => 25,
+        Variant::Brazilian => 26,
+        Variant::Canadian => 27,
+    }
+}
+
+/// The variant of a `GameType` tag. Only the number at the start is used, any
+/// board layout after it is ignored.
+fn variant_for_game_type(tag: &str) -> Result<Variant> {
+    let number = tag.split(',').next().unwrap_or_default().trim();
+    Variant::ALL
+        .into_iter()
+        .find(|variant| game_type(*variant).to_string() == number)
+        .ok_or_else(|| anyhow!("Unsupported GameType {number}"))
+}
+
+/// Lines of move text are wrapped to fit within this many characters
+const LINE_WIDTH: usize = 79;
+
+/// The result of a game. PDN gives results from the point of view of the
+/// player who moves first.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum PdnResult {
+    FirstPlayerWins,
+    SecondPlayerWins,
+    Draw,
+    /// The game is unfinished, or its result isn't known
+    #[default]
+    Unknown,
+}
+
+impl PdnResult {
+    fn parse(text: &str) -> Option<Self> {
+        match text {
+            "1-0" | "2-0" => Some(PdnResult::FirstPlayerWins),
+            "0-1" | "0-2" => Some(PdnResult::SecondPlayerWins),
+            "1/2-1/2" | "1-1" => Some(PdnResult::Draw),
+            "*" => Some(PdnResult::Unknown),
+            _ => None,
+        }
+    }
+
+    /// The result of a game which has reached `board` with `player` to move.
+    /// A player who can't move has lost, otherwise the game isn't over.
+    pub fn from_board(board: &Board, player: Player) -> Self {
+        if !board.get_legal_moves(player).is_empty() {
+            PdnResult::Unknown
+        } else if player == board.rules().first_player() {
+            PdnResult::SecondPlayerWins
+        } else {
+            PdnResult::FirstPlayerWins
+        }
+    }
+
+    /// The result of a finished game, given the player who moved first
+    pub fn from_game_result(result: &GameResult, first_player: Player) -> Self {
+        match result.winner() {
+            Some(winner) if winner == first_player => PdnResult::FirstPlayerWins,
+            Some(_) => PdnResult::SecondPlayerWins,
+            None => PdnResult::Draw,
+        }
+    }
+}
+
+impl fmt::Display for PdnResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            PdnResult::FirstPlayerWins => "1-0",
+            PdnResult::SecondPlayerWins => "0-1",
+            PdnResult::Draw => "1/2-1/2",
+            PdnResult::Unknown => "*",
+        };
+        write!(f, "{text}")
+    }
+}
+
+/// A move as written in a game record, which may not have been checked
+/// against the position yet
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PdnMove {
+    /// Every square the peice lands on, starting with the one it leaves. The
+    /// middle squares of a multi-jump may be left out.
+    pub squares: Vec<usize>,
+    pub capture: bool,
+    /// A comment written after the move
+    pub comment: Option<String>,
+    /// Other lines which could have been played instead of this move
+    pub variations: Vec<PdnLine>,
+}
+
+impl PdnMove {
+    /// Record a move made in a game of `variant`
+    pub fn from_move(this_move: &Move, variant: Variant) -> Self {
+        Self {
+            squares: this_move
+                .path()
+                .iter()
+                // SAFETY: moves only ever land on playable tiles
+                .map(|pos| pos.square_number(variant).unwrap())
+                .collect(),
+            capture: this_move.is_capture(),
+            ..Self::default()
+        }
+    }
+
+    /// Find the legal move on `board` which this describes
+    pub fn to_move(&self, board: &Board, player: Player) -> Result<Move> {
+        let path = self
+            .squares
+            .iter()
+            .map(|square| Position::from_square(*square, board.variant()))
+            .collect::<Result<Vec<_>>>()?;
+        board.find_move(player, &path)
+    }
+
+    fn parse(text: &str) -> Result<Self> {
+        // Strength annotations such as `!` or `?!` aren't kept
+        let text = text.trim_end_matches(['!', '?']);
+        let capture = text.contains('x');
+        let squares = text
+            .split(['-', 'x'])
+            .map(|square| square.parse())
+            .collect::<Result<Vec<usize>, _>>()
+            .map_err(|_| anyhow!("Invalid move {text}"))?;
+
+        if squares.len() < 2 {
+            bail!("Invalid move {text}");
+        }
+
+        Ok(Self {
+            squares,
+            capture,
+            ..Self::default()
+        })
+    }
+}
+
+impl fmt::Display for PdnMove {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let separator = if self.capture { "x" } else { "-" };
+        let squares: Vec<String> = self.squares.iter().map(usize::to_string).collect();
+        write!(f, "{}", squares.join(separator))
+    }
+}
+
+/// A sequence of moves, either the main line of a game or a variation
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PdnLine {
+    /// A comment written before the first move
+    pub comment: Option<String>,
+    pub moves: Vec<PdnMove>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PdnGame {
+    /// Tag pairs, in the order they were written
+    pub tags: Vec<(String, String)>,
+    pub line: PdnLine,
+    pub result: PdnResult,
+}
+
+impl PdnGame {
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Set a tag, replacing its value if it has already been set
+    pub fn set_tag(&mut self, name: &str, value: impl Into<String>) {
+        let value = value.into();
+        match self.tags.iter_mut().find(|(tag, _)| tag == name) {
+            Some((_, old)) => *old = value,
+            None => self.tags.push((name.to_string(), value)),
+        }
+    }
+
+    /// The variant the game is played in, from its `GameType` tag
+    pub fn variant(&self) -> Result<Variant> {
+        self.tag("GameType")
+            .map_or(Ok(Variant::English), variant_for_game_type)
+    }
+
+    /// The position the game starts from, and the player to move first. This
+    /// is the usual starting position unless the game has a `FEN` tag.
+    pub fn setup(&self) -> Result<(Board, Player)> {
+        let variant = self.variant()?;
+        match self.tag("FEN") {
+            Some(fen) => Board::from_fen(variant, fen),
+            None => {
+                let board = Board::with_variant(variant);
+                Ok((board, board.rules().first_player()))
+            }
+        }
+    }
+
+    /// The number of the first ply of the game, which is 1 when the second
+    /// player moves first from a set up position
+    fn first_ply(&self) -> usize {
+        match self.setup() {
+            Ok((board, player)) if player != board.rules().first_player() => 1,
+            _ => 0,
+        }
+    }
+
+    /// Play through the main line from the start of the game, checking each
+    /// move is legal. Returns the final board along with every move played.
+    pub fn replay(&self) -> Result<(Board, Vec<Move>)> {
+        let (mut board, mut player) = self.setup()?;
+        let first_ply = self.first_ply();
+        let mut moves = Vec::new();
+        for (ply, pdn_move) in self.line.moves.iter().enumerate() {
+            let number = (first_ply + ply) / 2 + 1;
+            let this_move = pdn_move
+                .to_move(&board, player)
+                .with_context(|| format!("Move {number} {pdn_move} can't be played"))?;
+            board.apply_move(player, &this_move);
+            moves.push(this_move);
+            player = !player;
+        }
+
+        Ok((board, moves))
+    }
+
+    /// Read every game from a PDN file
+    pub fn load_all<P: AsRef<Path>>(path: P) -> Result<Vec<Self>> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read games from {}", path.display()))?;
+        parse_games(&text)
+    }
+
+    /// Read game number `number` from a PDN file, counting from 1. The number
+    /// can be left out when the file only holds one game.
+    pub fn load<P: AsRef<Path>>(path: P, number: Option<usize>) -> Result<Self> {
+        pick_game(Self::load_all(path)?, number)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        fs::write(path, self.to_string())
+            .with_context(|| format!("Failed to write game to {}", path.display()))
+    }
+}
+
+/// A piece of a PDN file
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Tag(String, String),
+    Comment(String),
+    StartVariation,
+    EndVariation,
+    Move(PdnMove),
+    Result(PdnResult),
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => (),
+            '[' => {
+                let mut tag = String::new();
+                let mut in_quotes = false;
+                loop {
+                    match chars.next() {
+                        Some('\\') if in_quotes => tag.extend(chars.next()),
+                        Some('"') => {
+                            in_quotes = !in_quotes;
+                            tag.push('"');
+                        }
+                        Some(']') if !in_quotes => break,
+                        Some(c) => tag.push(c),
+                        None => bail!("Unterminated tag [{tag}"),
+                    }
+                }
+
+                let (name, value) = tag
+                    .trim()
+                    .split_once(char::is_whitespace)
+                    .ok_or_else(|| anyhow!("Expected [Name \"value\"], found [{tag}]"))?;
+                let value = value
+                    .trim()
+                    .strip_prefix('"')
+                    .and_then(|value| value.strip_suffix('"'))
+                    .ok_or_else(|| anyhow!("Tag {name} must have a quoted value"))?;
+                tokens.push(Token::Tag(name.to_string(), value.to_string()));
+            }
+            '{' => {
+                let mut comment = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => comment.push(c),
+                        None => bail!("Unterminated comment {{{comment}"),
+                    }
+                }
+                tokens.push(Token::Comment(comment.trim().to_string()));
+            }
+            // Comments to the end of the line
+            ';' => {
+                let comment: String = chars.by_ref().take_while(|c| *c != '\n').collect();
+                tokens.push(Token::Comment(comment.trim().to_string()));
+            }
+            '(' => tokens.push(Token::StartVariation),
+            ')' => tokens.push(Token::EndVariation),
+            c => {
+                let mut word = String::from(c);
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace() && !"[]{}();".contains(*c))
+                {
+                    word.push(c);
+                }
+
+                // Numeric annotation glyphs such as `$1` aren't kept
+                if word.starts_with('$') {
+                    continue;
+                }
+                if let Some(result) = PdnResult::parse(&word) {
+                    tokens.push(Token::Result(result));
+                    continue;
+                }
+
+                // Move numbers, which may be joined to the move after them
+                let this_move = match word.rfind('.') {
+                    Some(idx) if word[..idx].trim_end_matches('.').parse::<usize>().is_ok() => {
+                        &word[idx + 1..]
+                    }
+                    _ => word.as_str(),
+                };
+                if !this_move.is_empty() {
+                    tokens.push(Token::Move(PdnMove::parse(this_move)?));
+                }
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Read moves up to the end of the current line, which is either the end of
+/// a variation or the result of the game
+fn parse_line(tokens: &mut std::iter::Peekable<std::vec::IntoIter<Token>>) -> Result<PdnLine> {
+    let mut line = PdnLine::default();
+
+    while let Some(token) = tokens.next_if(|token| {
+        !matches!(
+            token,
+            Token::EndVariation | Token::Result(_) | Token::Tag(..)
+        )
+    }) {
+        match token {
+            Token::Comment(comment) => {
+                let target = match line.moves.last_mut() {
+                    Some(last) => &mut last.comment,
+                    None => &mut line.comment,
+                };
+                match target {
+                    Some(existing) => {
+                        existing.push(' ');
+                        existing.push_str(&comment);
+                    }
+                    None => *target = Some(comment),
+                }
+            }
+            Token::StartVariation => {
+                let variation = parse_line(tokens)?;
+                if tokens.next() != Some(Token::EndVariation) {
+                    bail!("Unterminated variation");
+                }
+                line.moves
+                    .last_mut()
+                    .ok_or_else(|| anyhow!("A variation must follow a move"))?
+                    .variations
+                    .push(variation);
+            }
+            Token::Move(this_move) => line.moves.push(this_move),
+            _ => unreachable!(),
+        }
+    }
+
+    Ok(line)
+}
+
+/// Take game number `number` from `games`, see `PdnGame::load`
+fn pick_game(games: Vec<PdnGame>, number: Option<usize>) -> Result<PdnGame> {
+    let count = games.len();
+    match (number, count) {
+        (_, 0) => bail!("No games found"),
+        (None, 1) => Ok(games.into_iter().next().unwrap()),
+        (None, _) => bail!("There are {count} games, choose one by its number"),
+        (Some(number), _) => number
+            .checked_sub(1)
+            .and_then(|idx| games.into_iter().nth(idx))
+            .ok_or_else(|| anyhow!("There is no game {number}, there are {count} games")),
+    }
+}
+
+/// Read every game in a PDN file
+pub fn parse_games(text: &str) -> Result<Vec<PdnGame>> {
+    let mut tokens = tokenize(text)?.into_iter().peekable();
+    let mut games = Vec::new();
+
+    while tokens.peek().is_some() {
+        let mut game = PdnGame::default();
+        while let Some(Token::Tag(name, value)) =
+            tokens.next_if(|token| matches!(token, Token::Tag(..)))
+        {
+            game.tags.push((name, value));
+        }
+
+        game.line = parse_line(&mut tokens)?;
+        game.result = match tokens.next() {
+            Some(Token::Result(result)) => result,
+            Some(Token::EndVariation) => bail!("Unexpected ) in game {}", games.len() + 1),
+            // Without a result at the end of the moves, use the result tag
+            _ => game
+                .tag("Result")
+                .and_then(PdnResult::parse)
+                .unwrap_or_default(),
+        };
+        games.push(game);
+    }
+
+    Ok(games)
+}
+
+impl FromStr for PdnGame {
+    type Err = anyhow::Error;
+
+    /// Read a single game
+    fn from_str(s: &str) -> Result<Self> {
+        let mut games = parse_games(s)?;
+        match games.len() {
+            1 => Ok(games.remove(0)),
+            0 => Err(anyhow!("No game found")),
+            n => Err(anyhow!("Expected one game, found {n}")),
+        }
+    }
+}
+
+/// Write the moves of `line`, the first of which is played on `ply`
+fn write_line(line: &PdnLine, ply: usize, words: &mut Vec<String>) {
+    if let Some(comment) = &line.comment {
+        words.push(format!("{{{}}}", comment.replace('}', ")")));
+    }
+
+    // The second players moves only need numbering when something has come
+    // between them and the first players move
+    let mut numbered = false;
+    for (idx, this_move) in line.moves.iter().enumerate() {
+        let ply = ply + idx;
+        let number = ply / 2 + 1;
+        if ply.is_multiple_of(2) {
+            words.push(format!("{number}."));
+        } else if !numbered {
+            words.push(format!("{number}..."));
+        }
+
+        words.push(this_move.to_string());
+        numbered = true;
+        if let Some(comment) = &this_move.comment {
+            words.push(format!("{{{}}}", comment.replace('}', ")")));
+            numbered = false;
+        }
+        for variation in &this_move.variations {
+            words.push("(".to_string());
+            write_line(variation, ply, words);
+            words.push(")".to_string());
+            numbered = false;
+        }
+    }
+}
+
+impl fmt::Display for PdnGame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, value) in &self.tags {
+            writeln!(
+                f,
+                "[{name} \"{}\"]",
+                value.replace('\\', "\\\\").replace('"', "\\\"")
+            )?;
+        }
+        if !self.tags.is_empty() {
+            writeln!(f)?;
+        }
+
+        let mut words = Vec::new();
+        write_line(&self.line, self.first_ply(), &mut words);
+        words.push(self.result.to_string());
+
+        // Brackets sit directly against what they enclose
+        let mut text = String::new();
+        let mut width = 0;
+        let mut after_open = false;
+        for word in words {
+            let joined = after_open || word == ")";
+            if !text.is_empty() && !joined {
+                if width + 1 + word.len() > LINE_WIDTH {
+                    text.push('\n');
+                    width = 0;
+                } else {
+                    text.push(' ');
+                    width += 1;
+                }
+            }
+            width += word.len();
+            after_open = word == "(";
+            text.push_str(&word);
+        }
+        writeln!(f, "{text}")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        board::Board,
+        game::{Game, GameMode},
+        player::Player,
+        r#move::Position,
+        result::{DrawReason, GameResult, WinReason},
+        rules::Variant,
+    };
+
+    use super::{game_type, parse_games, pick_game, PdnGame, PdnMove, PdnResult};
+
+    const GAME: &str = r#"
+[Event "Club \"Championship\""]
+[Black "Alice"]
+[White "Bob"]
+[GameType "21"]
+
+{A well known opening}
+1. 11-15 23-19 2. 8-11 22-17 (2... 22-18 3. 15x22 25x18 {Even}) 3. 9-13
+17-14 4. 10x17 $1 21x14! 0-1
+"#;
+
+    #[test]
+    fn test_game_types() {
+        for variant in Variant::ALL {
+            let game: PdnGame = format!("[GameType \"{},W,8,8,A0,0\"] *", game_type(variant))
+                .parse()
+                .unwrap();
+            assert_eq!(game.variant().unwrap(), variant);
+        }
+
+        // Russian games use the same numbered squares as english games, from
+        // the other side of the board
+        let game: PdnGame = "[GameType \"25\"] 1. 22-18 11-15 2. 18x11 8x15 *"
+            .parse()
+            .unwrap();
+        let (board, moves) = game.replay().unwrap();
+        assert_eq!(board.variant(), Variant::Russian);
+        assert_eq!(moves[0].to_string(), "f6 -> e5");
+        assert_eq!(board.get_remaining_peices(Player::White), 11);
+
+        // Games can start from a set up position
+        let game: PdnGame = "[FEN \"W:W18:B14,K1\"] 1... 18x9 2. 1-5 *".parse().unwrap();
+        let (board, moves) = game.replay().unwrap();
+        assert_eq!(moves.len(), 2);
+        assert_eq!(board.fen(Player::White), "W:W9:BK5");
+        assert!(game
+            .to_string()
+            .starts_with("[FEN \"W:W18:B14,K1\"]\n\n1... 18x9 2. 1-5"));
+
+        let unknown: PdnGame = "[GameType \"99\"] *".parse().unwrap();
+        assert!(unknown.replay().is_err());
+    }
+
+    #[test]
+    fn test_parse() {
+        let game: PdnGame = GAME.parse().unwrap();
+        assert_eq!(game.tag("Event"), Some("Club \"Championship\""));
+        assert_eq!(game.tag("black"), Some("Alice"));
+        assert_eq!(game.result, PdnResult::SecondPlayerWins);
+        assert_eq!(game.line.comment.as_deref(), Some("A well known opening"));
+        assert_eq!(game.line.moves.len(), 8);
+
+        let variation = &game.line.moves[3].variations[0];
+        assert_eq!(variation.moves.len(), 3);
+        assert!(variation.moves[1].capture);
+        assert_eq!(variation.moves[2].comment.as_deref(), Some("Even"));
+
+        let (board, moves) = game.replay().unwrap();
+        assert_eq!(moves.len(), 8);
+        assert_eq!(moves[4].to_string(), "g3 -> h4");
+        assert_eq!(board.get_remaining_peices(Player::Black), 11);
+        assert_eq!(board.get_remaining_peices(Player::White), 11);
+
+        // Multi-jumps may be written in full or by their ends
+        let jump = PdnMove::parse("15x22x29").unwrap();
+        assert_eq!(jump.squares, vec![15, 22, 29]);
+        assert!(jump.capture);
+
+        assert!("1. 11-15 ( 23-19".parse::<PdnGame>().is_err());
+        assert!("1. 11-15 23-19 2. 8-b4".parse::<PdnGame>().is_err());
+        assert!("[Event \"Unterminated]".parse::<PdnGame>().is_err());
+        let illegal: PdnGame = "1. 11-15 23-19 2. 12-13".parse().unwrap();
+        assert!(illegal.replay().is_err());
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let game: PdnGame = GAME.parse().unwrap();
+        let text = game.to_string();
+        assert!(text.contains("[Event \"Club \\\"Championship\\\"\"]"));
+        assert!(text.contains("2. 8-11 22-17 (2... 22-18 3. 15x22 25x18"));
+        assert_eq!(text.parse::<PdnGame>().unwrap(), game);
+
+        // Several games in one file
+        let games = parse_games(&format!("{GAME}\n{text}\n1. 9-13 *")).unwrap();
+        assert_eq!(games.len(), 3);
+        assert_eq!(games[2].result, PdnResult::Unknown);
+        assert!(games[2].tags.is_empty());
+
+        // A game has to be chosen from a file with more than one
+        assert_eq!(pick_game(games.clone(), Some(3)).unwrap(), games[2]);
+        assert!(pick_game(games.clone(), None).is_err());
+        assert!(pick_game(games.clone(), Some(0)).is_err());
+        assert!(pick_game(games.clone(), Some(4)).is_err());
+        assert_eq!(pick_game(vec![game.clone()], None).unwrap(), game);
+        assert!(pick_game(Vec::new(), None).is_err());
+    }
+
+    #[test]
+    fn test_game_record() {
+        let record: PdnGame = GAME.parse().unwrap();
+        let game = Game::from_pdn(&record, GameMode::HumanVsHuman).unwrap();
+        assert_eq!(game.get_current_player(), Player::Black);
+
+        // Only the main line is kept, and the game isn't over yet
+        let written = game.to_pdn();
+        assert_eq!(written.tag("Black"), Some("Human"));
+        assert_eq!(written.result, PdnResult::Unknown);
+        assert_eq!(written.line.moves.len(), record.line.moves.len());
+        for (written, read) in written.line.moves.iter().zip(&record.line.moves) {
+            assert_eq!(written.squares, read.squares);
+            assert_eq!(written.capture, read.capture);
+        }
+    }
+
+    #[test]
+    fn test_result_from_board() {
+        let mut board = Board::empty();
+        let pos = |s: &str| Position::from_str(s).unwrap();
+        board[pos("a1")].take_ownership(Player::Black);
+        board[pos("b2")].take_ownership(Player::White);
+        board[pos("c3")].take_ownership(Player::White);
+
+        assert_eq!(
+            PdnResult::from_board(&board, Player::Black),
+            PdnResult::SecondPlayerWins
+        );
+        assert_eq!(
+            PdnResult::from_board(&board, Player::White),
+            PdnResult::Unknown
+        );
+
+        // Results are always given for the first player, whoever that is
+        let result = GameResult::Win {
+            winner: Player::White,
+            reason: WinReason::Resignation,
+        };
+        assert_eq!(
+            PdnResult::from_game_result(&result, Player::Black),
+            PdnResult::SecondPlayerWins
+        );
+        assert_eq!(
+            PdnResult::from_game_result(&result, Player::White),
+            PdnResult::FirstPlayerWins
+        );
+        let draw = GameResult::Draw(DrawReason::Repetition);
+        assert_eq!(
+            PdnResult::from_game_result(&draw, Player::Black),
+            PdnResult::Draw
+        );
+    }
+}
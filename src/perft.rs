@@ -0,0 +1,133 @@
+//! Performance tests for the move generator. `perft` counts every position
+//! reachable in exactly `depth` moves, which can be compared against
+//! published totals to check the move generator is correct.
+
+use crate::{bitboard::Bitboard, board::Board, player::Player, r#move::Move};
+
+/// Count the leaf nodes of the game tree `depth` moves deep
+pub fn perft(board: &Board, player: Player, depth: usize) -> u64 {
+    if let Some(bitboard) = board.bitboard() {
+        return perft_bitboard(&bitboard, player, depth);
+    }
+
+    perft_board(board, player, depth)
+}
+
+/// Count the leaf nodes below each legal move, useful for finding which move
+/// a difference in node counts comes from. No moves are made at depth 0, so
+/// there is nothing to divide.
+pub fn divide(board: &Board, player: Player, depth: usize) -> Vec<(Move, u64)> {
+    if depth == 0 {
+        return Vec::new();
+    }
+
+    board
+        .get_legal_moves(player)
+        .into_iter()
+        .map(|root_move| {
+            let nodes = if depth == 1 {
+                1
+            } else {
+                let mut child = *board;
+                // The move was generated from this board, so must be legal
+                child.make_move_for(player, root_move.clone()).unwrap();
+                perft(&child, !player, depth - 1)
+            };
+            (root_move, nodes)
+        })
+        .collect()
+}
+
+fn perft_bitboard(bitboard: &Bitboard, player: Player, depth: usize) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+
+    let moves = bitboard.legal_moves(player);
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+
+    moves
+        .iter()
+        .map(|this_move| {
+            let mut child = *bitboard;
+            child.apply(player, this_move);
+            perft_bitboard(&child, !player, depth - 1)
+        })
+        .sum()
+}
+
+fn perft_board(board: &Board, player: Player, depth: usize) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+
+    let moves = board.generic_legal_moves(player);
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+
+    moves
+        .iter()
+        .map(|this_move| {
+            let mut child = *board;
+            child.apply_move(player, this_move);
+            perft_board(&child, !player, depth - 1)
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{board::Board, player::Player, rules::Variant};
+
+    use super::{divide, perft};
+
+    /// Published perft totals for English draughts from the starting position
+    const ENGLISH_PERFT: [u64; 11] = [
+        1, 7, 49, 302, 1469, 7361, 36768, 179740, 845931, 3963680, 18391564,
+    ];
+
+    #[test]
+    fn test_english_perft() {
+        let board = Board::new();
+
+        for (depth, expected) in ENGLISH_PERFT.iter().enumerate() {
+            assert_eq!(
+                perft(&board, Player::Black, depth),
+                *expected,
+                "depth {depth}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_generic_generator_perft() {
+        // Check the tile by tile move generator agrees with the bitboard
+        let board = Board::new();
+        for (depth, expected) in ENGLISH_PERFT.iter().enumerate().take(7) {
+            assert_eq!(super::perft_board(&board, Player::Black, depth), *expected);
+        }
+    }
+
+    #[test]
+    fn test_divide() {
+        let board = Board::new();
+        let counts = divide(&board, Player::Black, 3);
+
+        assert_eq!(counts.len(), 7);
+        assert_eq!(counts.iter().map(|(_, n)| n).sum::<u64>(), ENGLISH_PERFT[3]);
+
+        // Each move is a single node one move deep, and nothing is divided at
+        // depth 0, where the board itself is the only node
+        assert!(divide(&board, Player::Black, 1).iter().all(|(_, n)| *n == 1));
+        assert!(divide(&board, Player::Black, 0).is_empty());
+        assert_eq!(perft(&board, Player::Black, 0), 1);
+
+        let board = Board::with_variant(Variant::International);
+        assert_eq!(perft(&board, Player::White, 1), 9);
+        assert_eq!(perft(&board, Player::White, 2), 81);
+        assert_eq!(perft(&board, Player::White, 3), 658);
+    }
+}
@@ -0,0 +1,102 @@
+//! How a game ended, and why
+
+use std::fmt;
+
+use crate::player::Player;
+
+/// Why the losing player lost
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum WinReason {
+    /// Every one of the losers peices was captured
+    NoPeices,
+    /// The loser had peices left, but none of them could move
+    NoLegalMoves,
+    Resignation,
+    /// The loser ran out of time, in games played with a clock
+    TimeForfeit,
+}
+
+/// Why neither player won
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DrawReason {
+    /// Both players agreed to a draw
+    Agreement,
+    /// The same position came up too many times
+    Repetition,
+    /// Too many moves were played without the game making progress
+    MoveLimit,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GameResult {
+    Win { winner: Player, reason: WinReason },
+    Draw(DrawReason),
+}
+
+impl GameResult {
+    /// The winning player, or `None` for a draw
+    pub fn winner(&self) -> Option<Player> {
+        match self {
+            GameResult::Win { winner, .. } => Some(*winner),
+            GameResult::Draw(_) => None,
+        }
+    }
+
+    /// The points scored by `player`, one for a win and a half for a draw
+    pub fn points(&self, player: Player) -> f64 {
+        match self.winner() {
+            Some(winner) if winner == player => 1.0,
+            Some(_) => 0.0,
+            None => 0.5,
+        }
+    }
+}
+
+impl fmt::Display for GameResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameResult::Win { winner, reason } => {
+                let loser = !*winner;
+                let reason = match reason {
+                    WinReason::NoPeices => format!("{loser} has no peices left"),
+                    WinReason::NoLegalMoves => format!("{loser} has no legal moves"),
+                    WinReason::Resignation => format!("{loser} resigned"),
+                    WinReason::TimeForfeit => format!("{loser} ran out of time"),
+                };
+                write!(f, "{winner} wins, {reason}")
+            }
+            GameResult::Draw(reason) => {
+                let reason = match reason {
+                    DrawReason::Agreement => "by agreement",
+                    DrawReason::Repetition => "by repetition",
+                    DrawReason::MoveLimit => "by the move limit",
+                };
+                write!(f, "Draw {reason}")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::player::Player;
+
+    use super::{DrawReason, GameResult, WinReason};
+
+    #[test]
+    fn test_game_result() {
+        let result = GameResult::Win {
+            winner: Player::White,
+            reason: WinReason::NoPeices,
+        };
+        assert_eq!(result.winner(), Some(Player::White));
+        assert_eq!(result.points(Player::White), 1.0);
+        assert_eq!(result.points(Player::Black), 0.0);
+        assert_eq!(result.to_string(), "White wins, Black has no peices left");
+
+        let result = GameResult::Draw(DrawReason::Agreement);
+        assert_eq!(result.winner(), None);
+        assert_eq!(result.points(Player::Black), 0.5);
+        assert_eq!(result.to_string(), "Draw by agreement");
+    }
+}
@@ -0,0 +1,435 @@
+use std::{
+    fmt::{self, Debug, Display},
+    str::FromStr,
+};
+
+use anyhow::{anyhow, Result};
+use dialoguer::Select;
+
+use crate::player::Player;
+
+/// How a man which reaches the last row part way through a capture chain is
+/// treated
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CapturePromotion {
+    /// The man is crowned and the move ends immediately
+    EndsMove,
+    /// The man is crowned and carries on capturing as a king
+    ContinuesAsKing,
+    /// The man carries on capturing as a man, and is only crowned if the
+    /// chain finishes on the last row
+    ContinuesAsMan,
+}
+
+/// Which capture a player must choose when more than one is available
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CapturePriority {
+    /// Any capture may be chosen
+    Free,
+    /// The capture which takes the most peices must be chosen
+    MostPeices,
+    /// Italian rules, the capture which takes the most peices, then captures
+    /// made by a king, then the capture taking the most kings, then the
+    /// capture which meets a king earliest
+    Italian,
+}
+
+/// The rules of a draughts variant. Everything about the game which differs
+/// between variants is described here, and consulted by `Board` when
+/// generating and playing moves.
+pub trait RuleSet: Debug + Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// The number of tiles along each side of the board
+    fn board_size(&self) -> usize {
+        8
+    }
+
+    /// The number of rows each player fills at the start of the game
+    fn rows_per_player(&self) -> usize {
+        3
+    }
+
+    /// Test if peices may be placed on a given tile
+    fn is_playable(&self, x: usize, y: usize) -> bool {
+        (x + y).is_multiple_of(2)
+    }
+
+    /// The player who makes the first move of the game
+    fn first_player(&self) -> Player;
+
+    /// Whether men are allowed to capture backwards
+    fn men_capture_backwards(&self) -> bool;
+
+    /// Whether kings may move any distance along a diagonal
+    fn flying_kings(&self) -> bool;
+
+    /// Whether men are allowed to capture kings
+    fn men_capture_kings(&self) -> bool {
+        true
+    }
+
+    fn capture_promotion(&self) -> CapturePromotion;
+
+    fn capture_priority(&self) -> CapturePriority;
+
+    /// The number of moves each player may make without capturing or moving
+    /// a man before the game is drawn
+    fn draw_move_limit(&self) -> usize;
+
+    /// The number of times a position may occur before the game is drawn
+    fn repetition_limit(&self) -> usize {
+        3
+    }
+}
+
+/// English draughts, or American checkers
+#[derive(Debug, Copy, Clone)]
+pub struct English;
+
+impl RuleSet for English {
+    fn name(&self) -> &'static str {
+        "English draughts"
+    }
+
+    fn first_player(&self) -> Player {
+        Player::Black
+    }
+
+    fn men_capture_backwards(&self) -> bool {
+        false
+    }
+
+    fn flying_kings(&self) -> bool {
+        false
+    }
+
+    fn capture_promotion(&self) -> CapturePromotion {
+        CapturePromotion::EndsMove
+    }
+
+    fn capture_priority(&self) -> CapturePriority {
+        CapturePriority::Free
+    }
+
+    fn draw_move_limit(&self) -> usize {
+        40
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Russian;
+
+impl RuleSet for Russian {
+    fn name(&self) -> &'static str {
+        "Russian draughts"
+    }
+
+    fn first_player(&self) -> Player {
+        Player::White
+    }
+
+    fn men_capture_backwards(&self) -> bool {
+        true
+    }
+
+    fn flying_kings(&self) -> bool {
+        true
+    }
+
+    fn capture_promotion(&self) -> CapturePromotion {
+        CapturePromotion::ContinuesAsKing
+    }
+
+    fn capture_priority(&self) -> CapturePriority {
+        CapturePriority::Free
+    }
+
+    fn draw_move_limit(&self) -> usize {
+        15
+    }
+}
+
+/// International rules played on an 8x8 board
+#[derive(Debug, Copy, Clone)]
+pub struct Brazilian;
+
+impl RuleSet for Brazilian {
+    fn name(&self) -> &'static str {
+        "Brazilian draughts"
+    }
+
+    fn first_player(&self) -> Player {
+        Player::White
+    }
+
+    fn men_capture_backwards(&self) -> bool {
+        true
+    }
+
+    fn flying_kings(&self) -> bool {
+        true
+    }
+
+    fn capture_promotion(&self) -> CapturePromotion {
+        CapturePromotion::ContinuesAsMan
+    }
+
+    fn capture_priority(&self) -> CapturePriority {
+        CapturePriority::MostPeices
+    }
+
+    fn draw_move_limit(&self) -> usize {
+        20
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Italian;
+
+impl RuleSet for Italian {
+    fn name(&self) -> &'static str {
+        "Italian draughts"
+    }
+
+    /// The Italian board is mirrored, leaving a light tile in each players
+    /// left hand corner
+    fn is_playable(&self, x: usize, y: usize) -> bool {
+        !(x + y).is_multiple_of(2)
+    }
+
+    fn first_player(&self) -> Player {
+        Player::White
+    }
+
+    fn men_capture_backwards(&self) -> bool {
+        false
+    }
+
+    fn flying_kings(&self) -> bool {
+        false
+    }
+
+    fn men_capture_kings(&self) -> bool {
+        false
+    }
+
+    fn capture_promotion(&self) -> CapturePromotion {
+        CapturePromotion::EndsMove
+    }
+
+    fn capture_priority(&self) -> CapturePriority {
+        CapturePriority::Italian
+    }
+
+    fn draw_move_limit(&self) -> usize {
+        40
+    }
+}
+
+/// American pool checkers
+#[derive(Debug, Copy, Clone)]
+pub struct Pool;
+
+impl RuleSet for Pool {
+    fn name(&self) -> &'static str {
+        "Pool checkers"
+    }
+
+    fn first_player(&self) -> Player {
+        Player::Black
+    }
+
+    fn men_capture_backwards(&self) -> bool {
+        true
+    }
+
+    fn flying_kings(&self) -> bool {
+        true
+    }
+
+    fn capture_promotion(&self) -> CapturePromotion {
+        CapturePromotion::ContinuesAsMan
+    }
+
+    fn capture_priority(&self) -> CapturePriority {
+        CapturePriority::Free
+    }
+
+    fn draw_move_limit(&self) -> usize {
+        40
+    }
+}
+
+/// International draughts, played on a 10x10 board
+#[derive(Debug, Copy, Clone)]
+pub struct International;
+
+impl RuleSet for International {
+    fn name(&self) -> &'static str {
+        "International draughts"
+    }
+
+    fn board_size(&self) -> usize {
+        10
+    }
+
+    fn rows_per_player(&self) -> usize {
+        4
+    }
+
+    fn first_player(&self) -> Player {
+        Player::White
+    }
+
+    fn men_capture_backwards(&self) -> bool {
+        true
+    }
+
+    fn flying_kings(&self) -> bool {
+        true
+    }
+
+    fn capture_promotion(&self) -> CapturePromotion {
+        CapturePromotion::ContinuesAsMan
+    }
+
+    fn capture_priority(&self) -> CapturePriority {
+        CapturePriority::MostPeices
+    }
+
+    fn draw_move_limit(&self) -> usize {
+        25
+    }
+}
+
+/// Canadian draughts, International rules played on a 12x12 board
+#[derive(Debug, Copy, Clone)]
+pub struct Canadian;
+
+impl RuleSet for Canadian {
+    fn name(&self) -> &'static str {
+        "Canadian draughts"
+    }
+
+    fn board_size(&self) -> usize {
+        12
+    }
+
+    fn rows_per_player(&self) -> usize {
+        5
+    }
+
+    fn first_player(&self) -> Player {
+        Player::White
+    }
+
+    fn men_capture_backwards(&self) -> bool {
+        true
+    }
+
+    fn flying_kings(&self) -> bool {
+        true
+    }
+
+    fn capture_promotion(&self) -> CapturePromotion {
+        CapturePromotion::ContinuesAsMan
+    }
+
+    fn capture_priority(&self) -> CapturePriority {
+        CapturePriority::MostPeices
+    }
+
+    fn draw_move_limit(&self) -> usize {
+        25
+    }
+}
+
+/// Every rule set the engine knows how to play
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum Variant {
+    #[default]
+    English,
+    Russian,
+    Brazilian,
+    Italian,
+    Pool,
+    International,
+    Canadian,
+}
+
+impl Variant {
+    pub const ALL: [Variant; 7] = [
+        Variant::English,
+        Variant::Russian,
+        Variant::Brazilian,
+        Variant::Italian,
+        Variant::Pool,
+        Variant::International,
+        Variant::Canadian,
+    ];
+
+    /// A short name which never changes, used in save files
+    pub fn key(&self) -> &'static str {
+        match self {
+            Variant::English => "english",
+            Variant::Russian => "russian",
+            Variant::Brazilian => "brazilian",
+            Variant::Italian => "italian",
+            Variant::Pool => "pool",
+            Variant::International => "international",
+            Variant::Canadian => "canadian",
+        }
+    }
+
+    pub fn rules(&self) -> &'static dyn RuleSet {
+        match self {
+            Variant::English => &English,
+            Variant::Russian => &Russian,
+            Variant::Brazilian => &Brazilian,
+            Variant::Italian => &Italian,
+            Variant::Pool => &Pool,
+            Variant::International => &International,
+            Variant::Canadian => &Canadian,
+        }
+    }
+
+    pub fn select_variant() -> Result<Self> {
+        let options: Vec<&str> = Self::ALL.iter().map(|v| v.rules().name()).collect();
+        let selection = Select::new()
+            .with_prompt("Select a variant (use the arrow keys to make your selection)")
+            .items(&options)
+            .default(0)
+            .interact()?;
+
+        Ok(Self::ALL[selection])
+    }
+}
+
+impl Display for Variant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.rules().name())
+    }
+}
+
+impl FromStr for Variant {
+    type Err = anyhow::Error;
+
+    /// Find a variant by its name, either in full or just the first word,
+    /// e.g. "English draughts" or "english"
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        Self::ALL
+            .into_iter()
+            .find(|variant| {
+                let name = variant.rules().name();
+                name.eq_ignore_ascii_case(s)
+                    || variant.key().eq_ignore_ascii_case(s)
+                    || name
+                        .split_whitespace()
+                        .next()
+                        .is_some_and(|word| word.eq_ignore_ascii_case(s))
+            })
+            .ok_or_else(|| anyhow!("Unknown variant {s}"))
+    }
+}
@@ -0,0 +1,337 @@
+//! Save files, so a game can be put aside and finished later. Saves are text
+//! files of `name = value` lines, starting with the version of the format so
+//! that older saves can still be recognised if it changes. Options are saved
+//! by their keys rather than the names shown on screen, so renaming an option
+//! doesn't break old saves.
+
+use std::{fmt, fs, path::Path, str::FromStr, time::Duration};
+
+use anyhow::{anyhow, bail, Context, Result};
+
+use crate::{
+    clock::Clock, difficulty::AiSettings, game::GameMode, player::Player, r#move::Notation,
+    rules::Variant,
+};
+
+/// The version of the save file format written by this version of the game.
+/// Version 1 saves only held the position and the players, and can still be
+/// read.
+pub const VERSION: u32 = 2;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SaveFile {
+    pub variant: Variant,
+    pub mode: GameMode,
+    /// The position the game started from in PDN-FEN, including the player
+    /// who moved first
+    pub start: String,
+    /// Every move played since the start, in numeric notation
+    pub moves: Vec<String>,
+    /// The move limit chosen for the game, if it isn't the variants own
+    pub move_limit: Option<usize>,
+    /// The time each player had left, in timed games
+    pub clock: Option<Clock>,
+    pub notation: Notation,
+    /// The number of threads the AI searches with, if one was chosen
+    pub threads: Option<usize>,
+    /// The full paths to the files the AIs weights, tablebase and opening book
+    /// were loaded from
+    pub weights: Option<String>,
+    pub tablebase: Option<String>,
+    pub book: Option<String>,
+}
+
+impl SaveFile {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        fs::read_to_string(path)
+            .with_context(|| format!("Failed to read saved game from {}", path.display()))?
+            .parse()
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        fs::write(path, self.to_string())
+            .with_context(|| format!("Failed to save game to {}", path.display()))
+    }
+}
+
+impl FromStr for SaveFile {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut version = None;
+        let mut variant = None;
+        let mut mode = None;
+        let mut ais = Vec::new();
+        let mut start = None;
+        let mut moves = Vec::new();
+        let mut move_limit = None;
+        let mut clock = None;
+        let mut notation = Notation::default();
+        let mut threads = None;
+        let mut weights = None;
+        let mut tablebase = None;
+        let mut book = None;
+
+        for (line_number, line) in s.lines().enumerate() {
+            // Only whole lines are comments, as a `#` can be part of a path
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (name, value) = line
+                .split_once('=')
+                .ok_or_else(|| anyhow!("Expected `name = value` on line {}", line_number + 1))?;
+            let (name, value) = (name.trim(), value.trim());
+
+            // Nothing else can be trusted until the version is known
+            if version.is_none() && name != "version" {
+                bail!("Save file must start with its version");
+            }
+
+            match name {
+                "version" => {
+                    let number: u32 = value
+                        .parse()
+                        .with_context(|| format!("Invalid version on line {}", line_number + 1))?;
+                    if !(1..=VERSION).contains(&number) {
+                        bail!("Unsupported save file version {number}, expected {VERSION}");
+                    }
+                    version = Some(number);
+                }
+                "variant" => variant = Some(value.parse()?),
+                "mode" => mode = Some(value.to_string()),
+                "ai" => ais.push(value.parse::<AiSettings>()?),
+                "start" => start = Some(value.to_string()),
+                "moves" => moves.extend(value.split_whitespace().map(str::to_string)),
+                "move_limit" => {
+                    move_limit = Some(value.parse().with_context(|| {
+                        format!("Invalid move limit on line {}", line_number + 1)
+                    })?)
+                }
+                "clock" => {
+                    let context = || format!("Invalid clock on line {}", line_number + 1);
+                    clock = Some(parse_clock(value).with_context(context)?);
+                }
+                "notation" => notation = value.parse()?,
+                "threads" => {
+                    threads = Some(value.parse().with_context(|| {
+                        format!("Invalid thread count on line {}", line_number + 1)
+                    })?)
+                }
+                "weights" => weights = Some(value.to_string()),
+                "tablebase" => tablebase = Some(value.to_string()),
+                "book" => book = Some(value.to_string()),
+                _ => bail!("Unknown field {name} on line {}", line_number + 1),
+            }
+        }
+
+        Ok(Self {
+            variant: variant.ok_or_else(|| anyhow!("Save file is missing its variant"))?,
+            mode: GameMode::from_name(
+                &mode.ok_or_else(|| anyhow!("Save file is missing its game mode"))?,
+                &ais,
+            )?,
+            start: start.ok_or_else(|| anyhow!("Save file is missing its starting position"))?,
+            moves,
+            move_limit,
+            clock,
+            notation,
+            threads,
+            weights,
+            tablebase,
+            book,
+        })
+    }
+}
+
+/// Read a clock saved as the seconds black and white have left, followed by
+/// the increment
+fn parse_clock(value: &str) -> Result<Clock> {
+    let seconds = value
+        .split_whitespace()
+        .map(|seconds| Ok(Duration::try_from_secs_f64(seconds.parse()?)?))
+        .collect::<Result<Vec<_>>>()?;
+    let [black, white, increment] = seconds[..] else {
+        bail!("Expected the time left for each player and the increment");
+    };
+    Ok(Clock::with_remaining(black, white, increment))
+}
+
+impl fmt::Display for SaveFile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "# Checkers saved game")?;
+        writeln!(f, "version = {VERSION}")?;
+        writeln!(f, "variant = {}", self.variant.key())?;
+        writeln!(f, "mode = {}", self.mode.key())?;
+        for ai in self.mode.ais() {
+            writeln!(f, "ai = {}", ai.key())?;
+        }
+        writeln!(f, "start = {}", self.start)?;
+        if let Some(move_limit) = self.move_limit {
+            writeln!(f, "move_limit = {move_limit}")?;
+        }
+        if let Some(clock) = &self.clock {
+            let [black, white] = [Player::Black, Player::White].map(|p| clock.remaining(p));
+            writeln!(
+                f,
+                "clock = {} {} {}",
+                black.as_secs_f64(),
+                white.as_secs_f64(),
+                clock.increment().as_secs_f64()
+            )?;
+        }
+        writeln!(f, "notation = {}", self.notation)?;
+        if let Some(threads) = self.threads {
+            writeln!(f, "threads = {threads}")?;
+        }
+        for (name, path) in [
+            ("weights", &self.weights),
+            ("tablebase", &self.tablebase),
+            ("book", &self.book),
+        ] {
+            if let Some(path) = path {
+                writeln!(f, "{name} = {path}")?;
+            }
+        }
+        writeln!(f, "moves = {}", self.moves.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{env, time::Duration};
+
+    use crate::{
+        ballot::Deck,
+        book::OpeningBook,
+        clock::Clock,
+        difficulty::{AiSettings, Difficulty, Engine},
+        eval::EvalWeights,
+        game::{Game, GameMode},
+        r#move::Notation,
+        rules::Variant,
+    };
+
+    use super::SaveFile;
+
+    #[test]
+    fn test_save_and_resume() {
+        let first = AiSettings {
+            engine: Engine::MonteCarlo,
+            ..AiSettings::default()
+        };
+        let second = AiSettings {
+            difficulty: Difficulty::Beginner,
+            ..AiSettings::default()
+        };
+        let mut game = Game::with_variant(Variant::Pool, GameMode::AiVsAi(first, second));
+        game.set_move_limit(12);
+        for text in ["c3-d4", "f6-e5", "d4xf6"] {
+            let player = game.get_current_player();
+            let this_move = game.board().parse_move(player, text).unwrap();
+            game.play_move(this_move).unwrap();
+        }
+
+        let path = env::temp_dir().join(format!("checkers-save-{}", std::process::id()));
+        game.save(&path).unwrap();
+        let resumed = Game::resume(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(resumed.board(), game.board());
+        assert_eq!(resumed.get_current_player(), game.get_current_player());
+        assert_eq!(resumed.mode(), game.mode());
+        assert_eq!(resumed.moves(), game.moves());
+        assert_eq!(resumed.move_limit(), 12);
+        assert_eq!(resumed.save_file(), game.save_file());
+
+        // Options are written by their keys
+        let text = game.save_file().to_string();
+        assert!(text.contains("variant = pool\n"));
+        assert!(text.contains("mode = ai-ai\n"));
+        assert!(text.contains("ai = mcts, hard, balanced\n"));
+
+        // Games from a set up position keep it
+        let ballot = Deck::ElevenMan.ballot(5).unwrap();
+        let game = Game::with_ballot(ballot, GameMode::HumanVsHuman);
+        let resumed = Game::from_save_file(&game.save_file()).unwrap();
+        assert_eq!(resumed.board(), &ballot.board());
+    }
+
+    #[test]
+    fn test_resume_settings() {
+        let dir = env::temp_dir();
+        let id = std::process::id();
+        // A `#` in a path isn't taken for a comment
+        let weights_path = dir.join(format!("checkers#weights-{id}"));
+        let book_path = dir.join(format!("checkers-book-{id}"));
+        let weights = EvalWeights {
+            king: 150,
+            ..EvalWeights::default()
+        };
+        weights.save(&weights_path).unwrap();
+        let book = OpeningBook::from_games(Variant::English, "11-15 23-19", 2).unwrap();
+        book.save(&book_path).unwrap();
+
+        let mut game = Game::with_variant(Variant::English, GameMode::HumanVsHuman);
+        game.load_weights(weights_path.to_str().unwrap()).unwrap();
+        // Paths are saved in full, so the game can be resumed from anywhere
+        let roundabout = dir.join(".").join(book_path.file_name().unwrap());
+        game.load_book(roundabout.to_str().unwrap()).unwrap();
+        let full_path = |path| std::fs::canonicalize(path).unwrap().display().to_string();
+        assert_eq!(game.save_file().book, Some(full_path(&book_path)));
+        assert_eq!(game.save_file().weights, Some(full_path(&weights_path)));
+        game.set_notation(Notation::Numeric);
+        game.search_config_mut().threads = 3;
+        let clock = Clock::with_remaining(
+            Duration::from_millis(61_500),
+            Duration::from_secs(90),
+            Duration::from_secs(2),
+        );
+        game.set_clock(clock);
+
+        let save: SaveFile = game.save_file().to_string().parse().unwrap();
+        let mut resumed = Game::from_save_file(&save).unwrap();
+        std::fs::remove_file(&weights_path).unwrap();
+        std::fs::remove_file(&book_path).unwrap();
+
+        assert_eq!(resumed.save_file(), game.save_file());
+        assert_eq!(resumed.clock(), Some(&clock));
+        assert_eq!(resumed.search_config_mut().threads, 3);
+        assert_eq!(resumed.search_config_mut().weights, weights);
+        assert_eq!(resumed.mcts_config_mut().weights, weights);
+
+        // A file which has gone missing stops the game being resumed
+        assert!(Game::from_save_file(&save).is_err());
+    }
+
+    #[test]
+    fn test_invalid_files() {
+        let valid = "version = 1\nvariant = English\nmode = Human vs Human\nstart = B:W21:B1\n";
+        assert!(valid.parse::<SaveFile>().is_ok());
+
+        assert!(valid
+            .replace("version = 1", "version = 3")
+            .parse::<SaveFile>()
+            .is_err());
+        assert!(valid
+            .replace("version = 1\n", "")
+            .parse::<SaveFile>()
+            .is_err());
+        assert!(valid
+            .replace("Human vs Human", "Human vs AI")
+            .parse::<SaveFile>()
+            .is_err());
+        assert!(format!("{valid}colour = red").parse::<SaveFile>().is_err());
+        assert!(format!("{valid}move_limit = -3")
+            .parse::<SaveFile>()
+            .is_err());
+        assert!(format!("{valid}clock = 60 60").parse::<SaveFile>().is_err());
+
+        // Moves are checked when the game is resumed
+        let illegal: SaveFile = format!("{valid}moves = 1-9").parse().unwrap();
+        assert!(Game::from_save_file(&illegal).is_err());
+    }
+}
@@ -0,0 +1,916 @@
+//! Game tree search used by the AI players.
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use rand::Rng;
+
+use crate::{
+    board::Board,
+    eval::{self, EvalWeights},
+    history::{self, PositionHistory},
+    player::Player,
+    r#move::Move,
+    tablebase::{Outcome, Tablebase},
+    transposition::{Bound, Entry, TranspositionTable},
+    zobrist,
+};
+
+/// The score of a position where the side to move has already won. Wins found
+/// closer to the root score slightly higher, so the quickest win is preferred.
+pub const WIN_SCORE: isize = 1_000_000;
+
+/// Scores beyond this are a forced win or loss
+pub const WIN_THRESHOLD: isize = WIN_SCORE - 1_000;
+
+/// The deepest a search will ever go
+pub const MAX_DEPTH: usize = 64;
+
+/// How much time the AI is allowed to spend thinking about a move
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TimeControl {
+    /// Search to the full depth, however long it takes
+    Infinite,
+    /// Spend at most this long on each move
+    PerMove(Duration),
+    /// Share the time left on the players clock between the moves still to
+    /// be played
+    Clock {
+        remaining: Duration,
+        increment: Duration,
+        /// The number of moves left before the clock is topped up, if known
+        moves_to_go: Option<usize>,
+    },
+}
+
+impl TimeControl {
+    /// The number of moves assumed to be left in the game when playing on a
+    /// clock which is never topped up
+    const EXPECTED_MOVES: u32 = 30;
+
+    /// The longest a search may run for, or `None` if it is unlimited
+    pub fn budget(&self) -> Option<Duration> {
+        match *self {
+            TimeControl::Infinite => None,
+            TimeControl::PerMove(budget) => Some(budget),
+            TimeControl::Clock {
+                remaining,
+                increment,
+                moves_to_go,
+            } => {
+                let moves = moves_to_go.map_or(Self::EXPECTED_MOVES, |n| n.max(1) as u32);
+                // Never plan to use more than half of what is left, so there
+                // is always time for the following moves
+                Some((remaining / moves + increment * 3 / 4).min(remaining / 2))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SearchConfig {
+    /// The number of moves to look ahead
+    pub depth: usize,
+    pub time: TimeControl,
+    /// The size of the transposition table in megabytes
+    pub hash_mb: usize,
+    pub weights: EvalWeights,
+    /// The most a random amount added to each evaluation can be, used to
+    /// make weaker AIs less predictable
+    pub eval_noise: isize,
+    /// The number of threads searching at once. With more than one thread the
+    /// result depends on timing, so tests should only use a single thread.
+    pub threads: usize,
+    /// The number of quiet moves each player can make before the game is
+    /// drawn, or `None` to use the limit of the variant being played
+    pub move_limit: Option<usize>,
+}
+
+impl SearchConfig {
+    /// Search to exactly `depth` moves, without a time limit
+    pub fn fixed_depth(depth: usize) -> Self {
+        Self {
+            depth,
+            time: TimeControl::Infinite,
+            threads: 1,
+            ..Self::default()
+        }
+    }
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            depth: MAX_DEPTH,
+            time: TimeControl::PerMove(Duration::from_secs(1)),
+            hash_mb: 16,
+            weights: EvalWeights::default(),
+            eval_noise: 0,
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            move_limit: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchResult {
+    /// The best move found, or `None` if the player has no legal moves
+    pub best_move: Option<Move>,
+    /// The score of the position from the point of view of the moving player
+    pub score: isize,
+    /// The sequence of moves both players are expected to make
+    pub principal_variation: Vec<Move>,
+    /// The number of positions visited
+    pub nodes: u64,
+    /// The deepest search which finished before running out of time
+    pub depth: usize,
+}
+
+/// A negamax search with alpha-beta pruning, deepened one move at a time
+/// until it reaches the maximum depth or runs out of time. With more than one
+/// thread, helper threads search the same position at the same time (Lazy
+/// SMP), filling the shared transposition table with results the main thread
+/// can use.
+pub struct Searcher {
+    config: SearchConfig,
+    table: TranspositionTable,
+    tablebase: Option<Arc<Tablebase>>,
+    history: Option<PositionHistory>,
+}
+
+impl Searcher {
+    pub fn new(config: SearchConfig) -> Self {
+        Self {
+            config,
+            table: TranspositionTable::new(config.hash_mb),
+            tablebase: None,
+            history: None,
+        }
+    }
+
+    /// Look up positions with few enough peices in `tablebase` instead of
+    /// searching them
+    pub fn with_tablebase(mut self, tablebase: Arc<Tablebase>) -> Self {
+        self.tablebase = Some(tablebase);
+        self
+    }
+
+    /// Score lines which repeat a position from earlier in the game, or run
+    /// into the move limit, as draws. The history must end with the position
+    /// being searched, or it is ignored.
+    pub fn with_history(mut self, history: PositionHistory) -> Self {
+        self.history = Some(history);
+        self
+    }
+
+    /// Find the best move for `player`. The result always comes from the
+    /// deepest search which was able to finish.
+    pub fn search(&mut self, board: &Board, player: Player) -> SearchResult {
+        let deadline = self
+            .config
+            .time
+            .budget()
+            .map(|budget| Instant::now() + budget);
+        let stop = AtomicBool::new(false);
+        let history = self
+            .history
+            .clone()
+            .filter(|history| history.current() == history::key(board, player))
+            .unwrap_or_else(|| PositionHistory::new(board, player));
+
+        thread::scope(|scope| {
+            let helpers: Vec<_> = (1..self.config.threads.max(1))
+                .map(|id| {
+                    let mut helper = Worker::new(self, &stop, deadline, id, history.clone());
+                    scope.spawn(move || {
+                        helper.iterate(board, player);
+                        helper.nodes
+                    })
+                })
+                .collect();
+
+            let mut main = Worker::new(self, &stop, deadline, 0, history);
+            let mut result = main.iterate(board, player);
+            stop.store(true, Ordering::Relaxed);
+
+            for helper in helpers {
+                result.nodes += helper.join().unwrap();
+            }
+            result
+        })
+    }
+}
+
+/// The state of the search on one thread
+struct Worker<'a> {
+    config: &'a SearchConfig,
+    table: &'a TranspositionTable,
+    tablebase: Option<&'a Tablebase>,
+    /// Set once the main thread has finished, telling the helpers to stop
+    stop: &'a AtomicBool,
+    /// 0 for the main thread, helpers are numbered from 1
+    id: usize,
+    nodes: u64,
+    deadline: Option<Instant>,
+    aborted: bool,
+    /// The deepest iteration which has finished in the current search
+    completed_depth: usize,
+    /// The positions leading to the one being searched, from the start of
+    /// the game
+    history: PositionHistory,
+    /// The earliest ply which a line scored as a draw by repetition or the
+    /// move limit depends on, negative for positions from before the search.
+    /// A score which depends on how its position was reached is kept out of
+    /// the transposition table.
+    draw_reach: isize,
+}
+
+impl<'a> Worker<'a> {
+    /// The number of nodes searched between checks of the clock
+    const CLOCK_CHECK_INTERVAL: u64 = 1024;
+
+    fn new(
+        searcher: &'a Searcher,
+        stop: &'a AtomicBool,
+        deadline: Option<Instant>,
+        id: usize,
+        history: PositionHistory,
+    ) -> Self {
+        Self {
+            config: &searcher.config,
+            table: &searcher.table,
+            tablebase: searcher.tablebase.as_deref(),
+            stop,
+            id,
+            nodes: 0,
+            deadline,
+            aborted: false,
+            completed_depth: 0,
+            history,
+            draw_reach: isize::MAX,
+        }
+    }
+
+    fn is_main(&self) -> bool {
+        self.id == 0
+    }
+
+    /// Deepen the search one move at a time
+    fn iterate(&mut self, board: &Board, player: Player) -> SearchResult {
+        let mut result = SearchResult {
+            best_move: None,
+            score: 0,
+            principal_variation: Vec::new(),
+            nodes: 0,
+            depth: 0,
+        };
+
+        for depth in 1..=self.config.depth.max(1) {
+            // Half of the helpers search one move deeper than the main
+            // thread, so they fill the table with results it will need next
+            let depth = (depth + self.id % 2).min(self.config.depth.max(1));
+
+            let mut principal_variation = Vec::new();
+            let score = self.negamax(
+                board,
+                player,
+                depth,
+                0,
+                -WIN_SCORE - 1,
+                WIN_SCORE + 1,
+                &mut principal_variation,
+            );
+
+            if self.aborted {
+                break;
+            }
+
+            result = SearchResult {
+                best_move: principal_variation.first().cloned(),
+                score,
+                principal_variation: principal_variation.clone(),
+                nodes: self.nodes,
+                depth,
+            };
+            self.completed_depth = depth;
+
+            // Searching deeper will not change a forced result, or the only
+            // move available
+            let forced = board.count_legal_moves(player) <= 1;
+            if score.abs() > WIN_THRESHOLD
+                || (forced && self.deadline.is_some())
+                || result.best_move.is_none()
+            {
+                break;
+            }
+        }
+
+        result.nodes = self.nodes;
+        result
+    }
+
+    /// Check if the search has run out of time. The main threads first
+    /// iteration is always allowed to finish so there is a move to play.
+    fn out_of_time(&mut self) -> bool {
+        if !self.aborted && self.nodes.is_multiple_of(Self::CLOCK_CHECK_INTERVAL) {
+            if !self.is_main() {
+                // Helpers have nothing to return, so they stop as soon as the
+                // main thread is done
+                self.aborted = self.stop.load(Ordering::Relaxed);
+            }
+
+            if let Some(deadline) = self.deadline {
+                if self.completed_depth > 0 || !self.is_main() {
+                    self.aborted |= Instant::now() >= deadline;
+                }
+            }
+        }
+
+        self.aborted
+    }
+
+    /// Score `board` from the point of view of `player`, filling `pv` with the
+    /// best line found. Scores outside of `alpha..beta` are only bounds.
+    #[allow(clippy::too_many_arguments)]
+    fn negamax(
+        &mut self,
+        board: &Board,
+        player: Player,
+        depth: usize,
+        ply: usize,
+        mut alpha: isize,
+        beta: isize,
+        pv: &mut Vec<Move>,
+    ) -> isize {
+        pv.clear();
+
+        // The root is always searched so there is a move to return
+        if ply > 0 {
+            if let Some(outcome) = self.tablebase.and_then(|tb| tb.probe(board, player)) {
+                return match outcome {
+                    Outcome::Win(distance) => WIN_SCORE - (ply + distance as usize) as isize,
+                    Outcome::Loss(distance) => -WIN_SCORE + (ply + distance as usize) as isize,
+                    Outcome::Draw => 0,
+                };
+            }
+        }
+
+        if depth == 0 {
+            return self.quiescence(board, player, ply, alpha, beta);
+        }
+
+        self.nodes += 1;
+        if self.out_of_time() {
+            return 0;
+        }
+
+        let moves = board.get_legal_moves(player);
+        if moves.is_empty() {
+            // A player who cannot move has lost
+            return -WIN_SCORE + ply as isize;
+        }
+
+        let key = board.hash() ^ zobrist::side_key(player);
+        let entry = self.table.probe(key);
+        let table_move = entry
+            .and_then(|entry| entry.best_move)
+            .map(usize::from)
+            .filter(|idx| *idx < moves.len());
+
+        if let Some(entry) = entry {
+            // The root is always searched so there is a best move to return
+            if ply > 0 && usize::from(entry.depth) >= depth {
+                let score = score_from_table(entry.score, ply);
+                let (lower, upper) = match entry.bound {
+                    Bound::Exact => (score, score),
+                    Bound::Lower => (score, WIN_SCORE + 1),
+                    Bound::Upper => (-WIN_SCORE - 1, score),
+                };
+
+                // Entries never include draws by repetition or the move
+                // limit. After a quiet move the line could still run into
+                // one, which would bring the score closer to a draw.
+                let (lower, upper) = if self.history.quiet_moves() > 0 {
+                    (lower.min(0), upper.max(0))
+                } else {
+                    (lower, upper)
+                };
+
+                if lower == upper || lower >= beta {
+                    pv.extend(table_move.map(|idx| moves[idx].clone()));
+                    return lower;
+                }
+                if upper <= alpha {
+                    pv.extend(table_move.map(|idx| moves[idx].clone()));
+                    return upper;
+                }
+            }
+        }
+
+        // Try the best move from an earlier search of this position first, as
+        // it is likely to still be good and cause more cutoffs
+        let mut order: Vec<usize> = (0..moves.len()).collect();
+        if let Some(idx) = table_move {
+            order.swap(0, idx);
+        }
+
+        let original_alpha = alpha;
+        let outer_reach = std::mem::replace(&mut self.draw_reach, isize::MAX);
+        let mut child_pv = Vec::new();
+        let mut best_score = -WIN_SCORE - 1;
+        let mut best_move = order[0];
+
+        for idx in order {
+            let this_move = &moves[idx];
+            let mut child = *board;
+            child.apply_move(player, this_move);
+
+            // A position only needs to repeat once to be a draw, as whichever
+            // side is happy with a draw can repeat it again
+            self.history
+                .push_position(&child, !player, history::is_quiet(board, this_move));
+            let since = match self.history.last_repetition() {
+                Some(since) => Some(since),
+                None => {
+                    let rules = board.rules();
+                    let move_limit = self.config.move_limit.unwrap_or(rules.draw_move_limit());
+                    self.history
+                        .draw(rules, move_limit)
+                        .map(|_| self.history.quiet_moves())
+                }
+            };
+            let score = if let Some(since) = since {
+                self.draw_reach = self.draw_reach.min((ply + 1) as isize - since as isize);
+                child_pv.clear();
+                0
+            } else {
+                -self.negamax(
+                    &child,
+                    !player,
+                    depth - 1,
+                    ply + 1,
+                    -beta,
+                    -alpha,
+                    &mut child_pv,
+                )
+            };
+            self.history.pop();
+
+            if self.aborted {
+                self.draw_reach = outer_reach;
+                return 0;
+            }
+
+            if score > best_score {
+                best_score = score;
+                best_move = idx;
+            }
+
+            if score > alpha {
+                alpha = score;
+                pv.clear();
+                pv.push(this_move.clone());
+                pv.append(&mut child_pv);
+            }
+
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        // The same position reached by another line may not be a draw
+        let reach = self.draw_reach;
+        self.draw_reach = outer_reach.min(reach);
+        if reach < ply as isize {
+            return best_score;
+        }
+
+        let bound = if best_score <= original_alpha {
+            Bound::Upper
+        } else if best_score >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        self.table.store(
+            key,
+            Entry {
+                depth: depth as u8,
+                bound,
+                score: score_to_table(best_score, ply),
+                best_move: u8::try_from(best_move).ok(),
+            },
+        );
+
+        best_score
+    }
+
+    fn evaluate(&self, board: &Board, player: Player) -> isize {
+        let score = eval::evaluate(board, player, &self.config.weights);
+        if self.config.eval_noise > 0 {
+            let noise = self.config.eval_noise;
+            score + rand::thread_rng().gen_range(-noise..=noise)
+        } else {
+            score
+        }
+    }
+
+    /// Keep searching captures until the position is quiet, so positions are
+    /// never evaluated in the middle of an exchange. Captures are forced, so
+    /// every capture has to be searched rather than standing pat.
+    fn quiescence(
+        &mut self,
+        board: &Board,
+        player: Player,
+        ply: usize,
+        mut alpha: isize,
+        beta: isize,
+    ) -> isize {
+        self.nodes += 1;
+        if self.out_of_time() {
+            return 0;
+        }
+
+        if !board.has_capture(player) {
+            return if board.count_legal_moves(player) == 0 {
+                -WIN_SCORE + ply as isize
+            } else {
+                self.evaluate(board, player)
+            };
+        }
+
+        let mut best_score = -WIN_SCORE - 1;
+        for this_move in board.get_legal_moves(player) {
+            let mut child = *board;
+            child.apply_move(player, &this_move);
+
+            let score = -self.quiescence(&child, !player, ply + 1, -beta, -alpha);
+            if self.aborted {
+                return 0;
+            }
+
+            best_score = best_score.max(score);
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        best_score
+    }
+}
+
+/// Wins are scored by their distance from the root, but the table is shared
+/// between positions at different plies, so store them by their distance
+/// from the position instead
+fn score_to_table(score: isize, ply: usize) -> i32 {
+    let score = if score > WIN_THRESHOLD {
+        score + ply as isize
+    } else if score < -WIN_THRESHOLD {
+        score - ply as isize
+    } else {
+        score
+    };
+    score as i32
+}
+
+fn score_from_table(score: i32, ply: usize) -> isize {
+    let score = score as isize;
+    if score > WIN_THRESHOLD {
+        score - ply as isize
+    } else if score < -WIN_THRESHOLD {
+        score + ply as isize
+    } else {
+        score
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        board::Board,
+        history::PositionHistory,
+        player::Player,
+        r#move::{Move, Position},
+        rules::Variant,
+    };
+
+    use std::{
+        sync::Arc,
+        time::{Duration, Instant},
+    };
+
+    use crate::tablebase::{Outcome, Tablebase};
+
+    use super::{EvalWeights, SearchConfig, Searcher, TimeControl, WIN_SCORE, WIN_THRESHOLD};
+
+    /// Plain negamax without pruning, to check alpha-beta finds the same score.
+    /// Captures are followed past the maximum depth like the quiescence search.
+    fn minimax(board: &Board, player: Player, depth: usize, ply: usize) -> isize {
+        let moves = board.get_legal_moves(player);
+        if moves.is_empty() {
+            return -WIN_SCORE + ply as isize;
+        }
+        if depth == 0 && !board.has_capture(player) {
+            return board.evaluate_board(player);
+        }
+
+        moves
+            .iter()
+            .map(|m| {
+                let mut child = *board;
+                child.apply_move(player, m);
+                -minimax(&child, !player, depth.saturating_sub(1), ply + 1)
+            })
+            .max()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_matches_minimax() {
+        let mut board = Board::new();
+        let mut player = Player::Black;
+
+        for text in ["c3-d4", "f6-g5", "b2-c3", "g7-f6"] {
+            for depth in 1..5 {
+                let result = Searcher::new(SearchConfig::fixed_depth(depth)).search(&board, player);
+                assert_eq!(
+                    result.score,
+                    minimax(&board, player, depth, 0),
+                    "{text} {depth}"
+                );
+                // Lines ending in a transposition table hit are cut short
+                assert!(!result.principal_variation.is_empty());
+                assert!(result.principal_variation.len() <= depth);
+                assert_eq!(
+                    result.best_move.as_ref(),
+                    result.principal_variation.first()
+                );
+            }
+
+            let this_move = board.parse_move(player, text).unwrap();
+            board.make_move_for(player, this_move).unwrap();
+            player = !player;
+        }
+    }
+
+    #[test]
+    fn test_finds_forced_win() {
+        let mut board = Board::empty();
+        let pos = |s: &str| Position::from_str(s).unwrap();
+
+        // Black can win immediately by jumping both of whites peices
+        board[pos("b2")].take_ownership(Player::Black);
+        board[pos("c3")].take_ownership(Player::White);
+        board[pos("e5")].take_ownership(Player::White);
+        board[pos("h8")].take_ownership(Player::Black);
+
+        let result = Searcher::new(SearchConfig::fixed_depth(4)).search(&board, Player::Black);
+        assert!(result.score > WIN_THRESHOLD);
+        assert_eq!(
+            result.best_move,
+            Some(Move::chain(
+                vec![pos("b2"), pos("d4"), pos("f6")],
+                vec![pos("c3"), pos("e5")]
+            ))
+        );
+
+        // With no peices left, white has lost
+        board
+            .make_move_for(Player::Black, result.best_move.unwrap())
+            .unwrap();
+        let result = Searcher::new(SearchConfig::default()).search(&board, Player::White);
+        assert_eq!(result.best_move, None);
+        assert_eq!(result.score, -WIN_SCORE);
+    }
+
+    #[test]
+    fn test_time_control() {
+        let board = Board::new();
+        let config = SearchConfig {
+            depth: super::MAX_DEPTH,
+            time: TimeControl::PerMove(Duration::from_millis(200)),
+            ..SearchConfig::default()
+        };
+
+        let start = Instant::now();
+        let result = Searcher::new(config).search(&board, Player::Black);
+        assert!(start.elapsed() < Duration::from_secs(2));
+        assert!(result.depth >= 1 && result.depth < super::MAX_DEPTH);
+        assert!(result.best_move.is_some());
+        assert!(result.principal_variation.len() <= result.depth);
+
+        // A tiny budget still gives a move from the first iteration
+        let config = SearchConfig {
+            depth: super::MAX_DEPTH,
+            time: TimeControl::PerMove(Duration::ZERO),
+            ..SearchConfig::default()
+        };
+        let result = Searcher::new(config).search(&board, Player::Black);
+        assert!(result.best_move.is_some());
+
+        let clock = TimeControl::Clock {
+            remaining: Duration::from_secs(60),
+            increment: Duration::ZERO,
+            moves_to_go: Some(10),
+        };
+        assert_eq!(clock.budget(), Some(Duration::from_secs(6)));
+        let clock = TimeControl::Clock {
+            remaining: Duration::from_secs(1),
+            increment: Duration::from_secs(10),
+            moves_to_go: None,
+        };
+        assert_eq!(clock.budget(), Some(Duration::from_millis(500)));
+    }
+
+    #[test]
+    fn test_transposition_table() {
+        let board = Board::new();
+        let mut searcher = Searcher::new(SearchConfig::fixed_depth(6));
+
+        // A second search of the same position is answered mostly from the
+        // table, and agrees with the first
+        let first = searcher.search(&board, Player::Black);
+        let second = searcher.search(&board, Player::Black);
+        assert_eq!(first.score, second.score);
+        assert_eq!(first.best_move, second.best_move);
+        assert!(second.nodes < first.nodes);
+
+        // Using the table doesn't change the result
+        assert_eq!(first.score, minimax(&board, Player::Black, 6, 0));
+    }
+
+    #[test]
+    fn test_quiescence() {
+        let mut board = Board::empty();
+        let pos = |s: &str| Position::from_str(s).unwrap();
+
+        // Black has to capture on e5, but white recaptures from g7 leaving
+        // black a man down
+        board[pos("a1")].take_ownership(Player::Black);
+        board[pos("d4")].take_ownership(Player::Black);
+        board[pos("e5")].take_ownership(Player::White);
+        board[pos("g7")].take_ownership(Player::White);
+        board[pos("h8")].take_ownership(Player::White);
+
+        let capture = board.parse_move(Player::Black, "d4xf6").unwrap();
+        let mut after_capture = board;
+        after_capture.apply_move(Player::Black, &capture);
+        let man = EvalWeights::default().man;
+        assert!(-after_capture.evaluate_board(Player::White) > -man / 2);
+
+        let result = Searcher::new(SearchConfig::fixed_depth(1)).search(&board, Player::Black);
+        assert_eq!(result.best_move, Some(capture));
+        assert!(result.score < -man / 2);
+    }
+
+    #[test]
+    fn test_parallel_search() {
+        let board = Board::new();
+
+        // A single thread always searches the same way
+        let config = SearchConfig::fixed_depth(6);
+        let first = Searcher::new(config).search(&board, Player::Black);
+        let second = Searcher::new(config).search(&board, Player::Black);
+        assert_eq!(first, second);
+
+        let config = SearchConfig {
+            threads: 4,
+            ..SearchConfig::fixed_depth(6)
+        };
+        let result = Searcher::new(config).search(&board, Player::Black);
+        assert_eq!(result.depth, 6);
+        assert!(board
+            .get_legal_moves(Player::Black)
+            .contains(&result.best_move.unwrap()));
+
+        // Helpers searching the same position still find forced wins
+        let mut board = Board::empty();
+        let pos = |s: &str| Position::from_str(s).unwrap();
+        board[pos("b2")].take_ownership(Player::Black);
+        board[pos("c3")].take_ownership(Player::White);
+        board[pos("e5")].take_ownership(Player::White);
+        board[pos("h8")].take_ownership(Player::Black);
+        let result = Searcher::new(config).search(&board, Player::Black);
+        assert!(result.score > WIN_THRESHOLD);
+    }
+
+    #[test]
+    fn test_tablebase() {
+        let tablebase = Arc::new(Tablebase::generate(2, |_, _| ()).unwrap());
+        let mut board = Board::empty();
+        let pos = |s: &str| Position::from_str(s).unwrap();
+
+        // Black wins by hunting down whites peice, which takes far longer
+        // than a shallow search can see
+        board[pos("h2")].take_ownership(Player::Black);
+        board[pos("h2")].promote();
+        board[pos("c3")].take_ownership(Player::White);
+        let Some(Outcome::Win(distance)) = tablebase.probe(&board, Player::Black) else {
+            panic!("Expected black to win");
+        };
+        assert!(distance > 3);
+
+        let config = SearchConfig::fixed_depth(2);
+        let result = Searcher::new(config).search(&board, Player::Black);
+        assert!(result.score < WIN_THRESHOLD);
+
+        let result = Searcher::new(config)
+            .with_tablebase(tablebase.clone())
+            .search(&board, Player::Black);
+        assert_eq!(result.score, WIN_SCORE - distance as isize);
+
+        // The move chosen keeps the win just as close
+        let mut child = board;
+        child.apply_move(Player::Black, &result.best_move.unwrap());
+        assert_eq!(
+            tablebase.probe(&child, Player::White),
+            Some(Outcome::Loss(distance - 1))
+        );
+    }
+
+    #[test]
+    fn test_repetition_draw() {
+        // Black is a king down, but can repeat the position the game started
+        // in by going back the way it came
+        let (mut board, mut player) = Board::from_fen(Variant::English, "B:WK29,K30:BK1").unwrap();
+        let start = board;
+        let mut history = PositionHistory::new(&board, player);
+        for text in ["g1-h2", "h8-g7", "h2-g1", "g7-h8"] {
+            let this_move = board.parse_move(player, text).unwrap();
+            history.push(&board, player, &this_move);
+            board.apply_move(player, &this_move);
+            player = !player;
+        }
+        assert_eq!(board, start);
+
+        let config = SearchConfig::fixed_depth(2);
+        let result = Searcher::new(config).search(&board, player);
+        assert!(result.score < 0);
+
+        let result = Searcher::new(config)
+            .with_history(history)
+            .search(&board, player);
+        assert_eq!(result.score, 0);
+        assert_eq!(
+            result.best_move,
+            Some(board.parse_move(player, "g1-h2").unwrap())
+        );
+
+        // A history which doesn't lead to the position is ignored
+        let other = PositionHistory::new(&Board::new(), Player::Black);
+        let result = Searcher::new(config)
+            .with_history(other)
+            .search(&board, player);
+        assert!(result.score < 0);
+
+        // With a short enough move limit, the game is drawn before black
+        // can be caught
+        let config = SearchConfig {
+            move_limit: Some(1),
+            ..SearchConfig::fixed_depth(4)
+        };
+        assert_eq!(Searcher::new(config).search(&board, player).score, 0);
+    }
+
+    #[test]
+    fn test_repetition_table() {
+        // White is a king up, so going back to the position the game
+        // started in throws away its advantage
+        let (mut board, mut player) = Board::from_fen(Variant::English, "W:WK18,K27:BK5").unwrap();
+        let mut history = PositionHistory::new(&board, player);
+        for text in ["18-23", "5-9"] {
+            let this_move = board.parse_move(player, text).unwrap();
+            history.push(&board, player, &this_move);
+            board.apply_move(player, &this_move);
+            player = !player;
+        }
+
+        let config = SearchConfig::fixed_depth(4);
+        let without = Searcher::new(config).search(&board, player).score;
+        let with = Searcher::new(config)
+            .with_history(history.clone())
+            .search(&board, player)
+            .score;
+        assert_ne!(without, with);
+
+        // Draws found through the history aren't used once it is gone
+        let mut searcher = Searcher::new(config).with_history(history.clone());
+        assert_eq!(searcher.search(&board, player).score, with);
+        searcher.history = None;
+        assert_eq!(searcher.search(&board, player).score, without);
+
+        // A table filled by a search without the history doesn't hide the
+        // repetitions from a later search with it
+        let mut searcher = Searcher::new(config);
+        assert_eq!(searcher.search(&board, player).score, without);
+        searcher.history = Some(history);
+        assert_eq!(searcher.search(&board, player).score, with);
+    }
+}
@@ -0,0 +1,745 @@
+//! Endgame tablebases, giving the result of perfect play and how long it takes
+//! for every position with only a few peices left.
+//!
+//! Positions are split into tables by their material, the number of men and
+//! kings each player has. Tables only hold positions with Black to move, and
+//! positions with White to move are turned around to match. Each table is
+//! generated by retrograde analysis. Every position is visited once to find
+//! those with no moves, and those whose captures and promotions lead into
+//! tables which are already finished. Each time a position is resolved its
+//! predecessors are found by un-making moves, and the result spreads back to
+//! them one ply further on. Everything which is never resolved is a draw.
+//!
+//! Results ignore the move limit, so a win which needs more king moves in a
+//! row than the limit allows would really be a draw. Such long wins are rare
+//! with few peices, and the search treats the tables as exact anyway.
+//!
+//! Tables use the bitboard move generator, so only English draughts is
+//! supported.
+
+use std::{collections::BTreeMap, fs, path::Path};
+
+use anyhow::{bail, Context, Result};
+
+use crate::{
+    bitboard::{self, Bitboard},
+    board::Board,
+    player::Player,
+};
+
+/// The result of a position for the player to move, with perfect play from
+/// both sides. Distances are counted in plies.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Win(u8),
+    Loss(u8),
+    Draw,
+}
+
+/// Values are stored in a byte each, counting the moves made by the player
+/// to move. 0 is a draw, 1 to 127 are wins and 128 onwards are losses in
+/// `value - 128` moves. Wins always take an odd number of plies and losses an
+/// even number, so the number of plies can be worked out again.
+const DRAW: u8 = 0;
+const LOSS: u8 = 128;
+/// The longest result which can be stored, in plies
+const MAX_DISTANCE: u8 = 254;
+
+const MAGIC: &[u8; 4] = b"CKTB";
+const VERSION: u8 = 2;
+
+/// The squares each players men can stand on, every square apart from the
+/// row they would be crowned on
+const MEN_SQUARES: usize = 28;
+
+impl Outcome {
+    fn from_value(value: u8) -> Self {
+        match value {
+            DRAW => Outcome::Draw,
+            1..LOSS => Outcome::Win(2 * value - 1),
+            _ => Outcome::Loss(2 * (value - LOSS)),
+        }
+    }
+
+    fn value(&self) -> u8 {
+        match *self {
+            Outcome::Win(distance) => distance.div_ceil(2),
+            Outcome::Loss(distance) => LOSS + distance / 2,
+            Outcome::Draw => DRAW,
+        }
+    }
+}
+
+/// Pascal's triangle, up to choosing from every square on the board
+const BINOMIALS: [[usize; 33]; 33] = {
+    let mut table = [[0; 33]; 33];
+    let mut n = 0;
+    while n < 33 {
+        table[n][0] = 1;
+        let mut k = 1;
+        while k <= n {
+            table[n][k] = table[n - 1][k - 1] + table[n - 1][k];
+            k += 1;
+        }
+        n += 1;
+    }
+    table
+};
+
+/// The number of ways to choose `k` of `n` things
+fn binomial(n: usize, k: usize) -> usize {
+    if k > n {
+        return 0;
+    }
+    BINOMIALS[n][k]
+}
+
+/// Number a set of squares, counting each square by its place among the
+/// `available` squares
+fn rank(mut squares: u32, available: u32) -> usize {
+    let mut rank = 0;
+    let mut i = 0;
+    while squares != 0 {
+        let square = squares.trailing_zeros();
+        let below = (available & ((1 << square) - 1)).count_ones() as usize;
+        rank += binomial(below, i + 1);
+        squares &= squares - 1;
+        i += 1;
+    }
+    rank
+}
+
+/// Find the set of `count` squares numbered `rank` by `rank`
+fn unrank(mut rank: usize, count: usize, available: u32) -> u32 {
+    let mut slots = [0; 32];
+    let mut len = 0;
+    let mut rest = available;
+    while rest != 0 {
+        slots[len] = rest.trailing_zeros();
+        rest &= rest - 1;
+        len += 1;
+    }
+
+    let mut squares = 0;
+    for i in (0..count).rev() {
+        // Find the highest square whose count of sets fits in what is left
+        len -= 1;
+        while binomial(len, i + 1) > rank {
+            len -= 1;
+        }
+        rank -= binomial(len, i + 1);
+        squares |= 1 << slots[len];
+    }
+    squares
+}
+
+/// The number of each kind of peice in a position, which picks the table it
+/// is stored in. Black is always the player to move.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Material {
+    black_men: usize,
+    black_kings: usize,
+    white_men: usize,
+    white_kings: usize,
+}
+
+impl Material {
+    fn of(bitboard: &Bitboard) -> Self {
+        let count = |squares: u32| squares.count_ones() as usize;
+        Self {
+            black_men: count(bitboard.black & !bitboard.kings),
+            black_kings: count(bitboard.black & bitboard.kings),
+            white_men: count(bitboard.white & !bitboard.kings),
+            white_kings: count(bitboard.white & bitboard.kings),
+        }
+    }
+
+    /// Every material with `peices` peices in total, where both players have
+    /// at least one peice
+    fn all(peices: usize) -> Vec<Self> {
+        let mut materials = Vec::new();
+        for black in 1..peices {
+            for black_men in 0..=black {
+                for white_men in 0..=peices - black {
+                    materials.push(Self {
+                        black_men,
+                        black_kings: black - black_men,
+                        white_men,
+                        white_kings: peices - black - white_men,
+                    });
+                }
+            }
+        }
+        materials
+    }
+
+    fn men(&self) -> usize {
+        self.black_men + self.white_men
+    }
+
+    /// The materials Black's moves can lead to, apart from this one turned
+    /// around. Captures take one of Whites peices, and a man can be crowned.
+    /// Moves which do both, or capture more than one peice, lead to materials
+    /// which are reached from these.
+    fn successors(&self) -> Vec<Self> {
+        let mut successors = Vec::new();
+        if self.black_men > 0 {
+            successors.push(Self {
+                black_men: self.black_men - 1,
+                black_kings: self.black_kings + 1,
+                ..*self
+            });
+        }
+        // Capturing Whites last peice wins, which needs no table
+        if self.white_men + self.white_kings > 1 {
+            if self.white_men > 0 {
+                successors.push(Self {
+                    white_men: self.white_men - 1,
+                    ..*self
+                });
+            }
+            if self.white_kings > 0 {
+                successors.push(Self {
+                    white_kings: self.white_kings - 1,
+                    ..*self
+                });
+            }
+        }
+        successors
+    }
+
+    /// The material once the board has been turned around
+    fn flipped(&self) -> Self {
+        Self {
+            black_men: self.white_men,
+            black_kings: self.white_kings,
+            white_men: self.black_men,
+            white_kings: self.black_kings,
+        }
+    }
+
+    /// The number of positions in the table. Each players men are placed on
+    /// their own 28 squares, then the kings on whatever is left. Black and
+    /// White men can be placed on the same square, those positions are
+    /// skipped.
+    fn table_size(&self) -> usize {
+        let empty = 32 - self.men();
+        binomial(MEN_SQUARES, self.black_men)
+            * binomial(MEN_SQUARES, self.white_men)
+            * binomial(empty, self.black_kings)
+            * binomial(empty - self.black_kings, self.white_kings)
+    }
+
+    /// Get the index of a position in the table
+    fn index(&self, bitboard: &Bitboard) -> usize {
+        let black_men = bitboard.black & !bitboard.kings;
+        let white_men = bitboard.white & !bitboard.kings;
+        let black_kings = bitboard.black & bitboard.kings;
+        let men = black_men | white_men;
+        let empty = 32 - self.men();
+
+        let mut idx = rank(black_men, !bitboard::promotion_row(Player::Black));
+        idx = idx * binomial(MEN_SQUARES, self.white_men)
+            + rank(white_men, !bitboard::promotion_row(Player::White));
+        idx = idx * binomial(empty, self.black_kings) + rank(black_kings, !men);
+        idx * binomial(empty - self.black_kings, self.white_kings)
+            + rank(bitboard.white & bitboard.kings, !(men | black_kings))
+    }
+
+    /// Get the position at `idx` in the table, or `None` if it places men of
+    /// both colours on the same square
+    fn position(&self, idx: usize) -> Option<Bitboard> {
+        let empty = 32 - self.men();
+        let white_kings_count = binomial(empty - self.black_kings, self.white_kings);
+        let black_kings_count = binomial(empty, self.black_kings);
+        let white_men_count = binomial(MEN_SQUARES, self.white_men);
+
+        let white_kings_rank = idx % white_kings_count;
+        let idx = idx / white_kings_count;
+        let black_kings_rank = idx % black_kings_count;
+        let idx = idx / black_kings_count;
+        let white_men_rank = idx % white_men_count;
+        let black_men_rank = idx / white_men_count;
+
+        let black_men = unrank(
+            black_men_rank,
+            self.black_men,
+            !bitboard::promotion_row(Player::Black),
+        );
+        let white_men = unrank(
+            white_men_rank,
+            self.white_men,
+            !bitboard::promotion_row(Player::White),
+        );
+        if black_men & white_men != 0 {
+            return None;
+        }
+
+        let men = black_men | white_men;
+        let black_kings = unrank(black_kings_rank, self.black_kings, !men);
+        let white_kings = unrank(white_kings_rank, self.white_kings, !(men | black_kings));
+
+        Some(Bitboard {
+            black: black_men | black_kings,
+            white: white_men | white_kings,
+            kings: black_kings | white_kings,
+        })
+    }
+}
+
+/// Turn a position around if needed so that Black is the player to move
+fn black_to_move(bitboard: &Bitboard, player: Player) -> Bitboard {
+    match player {
+        Player::Black => *bitboard,
+        Player::White => bitboard.flipped(),
+    }
+}
+
+/// Find every position with Black to move which leads to `child`, with White
+/// to move, by a move which doesn't capture or crown a man
+fn predecessors(child: &Bitboard, found: &mut Vec<Bitboard>) {
+    found.clear();
+    let empty = child.empty();
+    let mut peices = child.black;
+    while peices != 0 {
+        let to = peices & peices.wrapping_neg();
+        peices &= peices - 1;
+
+        let is_king = child.kings & to != 0;
+        for direction in 0..4 {
+            // Men can only have come from behind
+            let backwards = bitboard::forward_directions(Player::White).contains(&direction);
+            if !is_king && !backwards {
+                continue;
+            }
+
+            let from = bitboard::step(to, direction) & empty;
+            if from == 0 {
+                continue;
+            }
+
+            let mut parent = *child;
+            parent.black ^= from | to;
+            if is_king {
+                parent.kings ^= from | to;
+            }
+
+            // Captures are mandatory, so the move couldn't have been played
+            // if one was available
+            if parent.jumpers(Player::Black) == 0 {
+                found.push(parent);
+            }
+        }
+    }
+}
+
+pub struct Tablebase {
+    /// The values of every position with Black to move, by material
+    tables: BTreeMap<Material, Vec<u8>>,
+    max_peices: usize,
+}
+
+impl Tablebase {
+    /// Generate tables for every position with up to `max_peices` peices,
+    /// calling `progress` after all of the tables for each number of peices
+    /// are finished
+    pub fn generate(max_peices: usize, mut progress: impl FnMut(&Self, usize)) -> Result<Self> {
+        let mut tablebase = Self {
+            tables: BTreeMap::new(),
+            max_peices: 0,
+        };
+
+        for peices in 2..=max_peices {
+            for material in Material::all(peices) {
+                tablebase.generate_material(material)?;
+            }
+
+            tablebase.max_peices = peices;
+            progress(&tablebase, peices);
+        }
+
+        Ok(tablebase)
+    }
+
+    /// The most peices a position can have and still be in the tablebase
+    pub fn max_peices(&self) -> usize {
+        self.max_peices
+    }
+
+    /// Every result for positions with `peices` peices
+    pub fn outcomes(&self, peices: usize) -> impl Iterator<Item = Outcome> + '_ {
+        Material::all(peices).into_iter().flat_map(move |material| {
+            self.tables
+                .get(&material)
+                .into_iter()
+                .flatten()
+                .enumerate()
+                .filter(move |(idx, _)| material.position(*idx).is_some())
+                .map(|(_, value)| Outcome::from_value(*value))
+        })
+    }
+
+    /// The result of a position with Black to move, which must be in a table
+    /// which is already finished
+    fn lookup(&self, bitboard: &Bitboard) -> Outcome {
+        if bitboard.black == 0 {
+            return Outcome::Loss(0);
+        }
+
+        let material = Material::of(bitboard);
+        Outcome::from_value(self.tables[&material][material.index(bitboard)])
+    }
+
+    /// Generate the table for `material`, once every table its moves lead to
+    /// has been generated
+    fn generate_material(&mut self, material: Material) -> Result<()> {
+        if self.tables.contains_key(&material) {
+            return Ok(());
+        }
+
+        // White's moves are Black's moves in the table turned around
+        for successor in material.successors().into_iter().chain(
+            material
+                .flipped()
+                .successors()
+                .into_iter()
+                .map(|m| m.flipped()),
+        ) {
+            self.generate_material(successor)?;
+        }
+        self.generate_tables(material)
+    }
+
+    /// Generate the table for `material` together with the table for the same
+    /// material turned around, as moves which don't capture or crown lead
+    /// from one to the other
+    fn generate_tables(&mut self, material: Material) -> Result<()> {
+        let mut materials = vec![material];
+        if material.flipped() != material {
+            materials.push(material.flipped());
+        }
+
+        // Positions from both tables are numbered one after the other
+        let offsets = [0, material.table_size()];
+        let total = materials.iter().map(Material::table_size).sum();
+        let locate = |bitboard: &Bitboard| {
+            let material = Material::of(bitboard);
+            let table = usize::from(material != materials[0]);
+            offsets[table] + material.index(bitboard)
+        };
+        let position = |idx: usize| {
+            let table = usize::from(idx >= offsets[1]);
+            materials[table].position(idx - offsets[table])
+        };
+
+        let mut values = vec![DRAW; total];
+        let mut resolved = vec![false; total];
+        // Moves which stay in these tables, and haven't been resolved as wins
+        // for the opponent yet
+        let mut remaining = vec![0u8; total];
+        // The longest loss the moves resolved so far have led to
+        let mut longest = vec![0u8; total];
+        // Positions with a move out of these tables which doesn't lose
+        let mut safe = vec![false; total];
+        // Positions waiting to be resolved at each distance, along with
+        // whether they are wins
+        let mut pending: Vec<Vec<(usize, bool)>> = vec![Vec::new(); u8::MAX as usize + 1];
+
+        for idx in 0..total {
+            let Some(bitboard) = position(idx) else {
+                resolved[idx] = true;
+                continue;
+            };
+
+            let child_material = Material::of(&bitboard).flipped();
+            let mut quickest_win = None;
+            for this_move in bitboard.legal_moves(Player::Black) {
+                let mut child = bitboard;
+                child.apply(Player::Black, &this_move);
+                let child = child.flipped();
+                if Material::of(&child) == child_material {
+                    remaining[idx] += 1;
+                    continue;
+                }
+
+                match self.lookup(&child) {
+                    Outcome::Loss(distance) => {
+                        let distance = distance + 1;
+                        quickest_win = Some(quickest_win.map_or(distance, |d: u8| d.min(distance)));
+                    }
+                    Outcome::Win(distance) => longest[idx] = longest[idx].max(distance + 1),
+                    Outcome::Draw => safe[idx] = true,
+                }
+            }
+
+            if let Some(distance) = quickest_win {
+                safe[idx] = true;
+                pending[distance as usize].push((idx, true));
+            } else if remaining[idx] == 0 && !safe[idx] {
+                pending[longest[idx] as usize].push((idx, false));
+            }
+        }
+
+        let mut parents = Vec::new();
+        for distance in 0..=u8::MAX {
+            for (idx, win) in std::mem::take(&mut pending[distance as usize]) {
+                if resolved[idx] {
+                    continue;
+                }
+                if distance > MAX_DISTANCE {
+                    bail!("Results for {material:?} are longer than {MAX_DISTANCE} plies");
+                }
+                resolved[idx] = true;
+                values[idx] = if win {
+                    Outcome::Win(distance)
+                } else {
+                    Outcome::Loss(distance)
+                }
+                .value();
+
+                // SAFETY: only positions which exist are ever resolved
+                let bitboard = position(idx).unwrap();
+                predecessors(&bitboard.flipped(), &mut parents);
+                for parent in parents.iter().map(locate) {
+                    if resolved[parent] {
+                        continue;
+                    }
+
+                    if win {
+                        remaining[parent] -= 1;
+                        longest[parent] = longest[parent].max(distance + 1);
+                        if remaining[parent] == 0 && !safe[parent] {
+                            pending[longest[parent] as usize].push((parent, false));
+                        }
+                    } else {
+                        pending[distance as usize + 1].push((parent, true));
+                    }
+                }
+            }
+        }
+
+        let (first, second) = values.split_at(offsets[1]);
+        self.tables.insert(material, first.to_vec());
+        if materials.len() > 1 {
+            self.tables.insert(material.flipped(), second.to_vec());
+        }
+        Ok(())
+    }
+
+    /// Look up the result of a position, if it is in the tablebase
+    pub fn probe(&self, board: &Board, player: Player) -> Option<Outcome> {
+        let bitboard = black_to_move(&board.bitboard()?, player);
+        let men_crowned = |player| {
+            let men = bitboard.peices(player) & !bitboard.kings;
+            men & bitboard::promotion_row(player) != 0
+        };
+        if men_crowned(Player::Black) || men_crowned(Player::White) {
+            return None;
+        }
+
+        let material = Material::of(&bitboard);
+        let table = self.tables.get(&material)?;
+        Some(Outcome::from_value(table[material.index(&bitboard)]))
+    }
+
+    /// Save the tablebase to a file. After a short header, each table is
+    /// written in order with a byte for every position. Positions are found
+    /// by their index, so nothing else needs to be stored.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let mut data = MAGIC.to_vec();
+        data.push(VERSION);
+        data.push(self.max_peices as u8);
+        for peices in 2..=self.max_peices {
+            for material in Material::all(peices) {
+                data.extend_from_slice(&self.tables[&material]);
+            }
+        }
+
+        let path = path.as_ref();
+        fs::write(path, data)
+            .with_context(|| format!("Failed to write tablebase to {}", path.display()))
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let data = fs::read(path)
+            .with_context(|| format!("Failed to read tablebase from {}", path.display()))?;
+
+        if data.len() < 6 || &data[..4] != MAGIC {
+            bail!("{} is not a tablebase", path.display());
+        }
+        if data[4] != VERSION {
+            bail!("Unsupported tablebase version {}", data[4]);
+        }
+
+        let max_peices = data[5] as usize;
+        let materials: Vec<Material> = (2..=max_peices).flat_map(Material::all).collect();
+        let expected = 6 + materials.iter().map(Material::table_size).sum::<usize>();
+        if data.len() != expected {
+            bail!(
+                "Tablebase {} should be {expected} bytes long, but is {} bytes",
+                path.display(),
+                data.len()
+            );
+        }
+
+        let mut tables = BTreeMap::new();
+        let mut rest = &data[6..];
+        for material in materials {
+            let (table, remaining) = rest.split_at(material.table_size());
+            tables.insert(material, table.to_vec());
+            rest = remaining;
+        }
+
+        Ok(Self { tables, max_peices })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{collections::BTreeMap, env};
+
+    use crate::{board::Board, player::Player, r#move::Position, rules::Variant};
+
+    use super::{Material, Outcome, Tablebase};
+
+    /// Check every result in every table agrees with the results of the moves
+    /// from it
+    fn check_tables(tablebase: &Tablebase) {
+        for (material, table) in &tablebase.tables {
+            for (idx, value) in table.iter().enumerate() {
+                let Some(bitboard) = material.position(idx) else {
+                    continue;
+                };
+                assert_eq!(Material::of(&bitboard), *material);
+                assert_eq!(material.index(&bitboard), idx);
+
+                let children: Vec<Outcome> = bitboard
+                    .legal_moves(Player::Black)
+                    .iter()
+                    .map(|this_move| {
+                        let mut child = bitboard;
+                        child.apply(Player::Black, this_move);
+                        tablebase.lookup(&child.flipped())
+                    })
+                    .collect();
+
+                match Outcome::from_value(*value) {
+                    Outcome::Win(distance) => {
+                        assert!(children.contains(&Outcome::Loss(distance - 1)));
+                        assert!(!children
+                            .iter()
+                            .any(|c| matches!(c, Outcome::Loss(d) if *d < distance - 1)));
+                    }
+                    Outcome::Loss(distance) => {
+                        assert!(children
+                            .iter()
+                            .all(|c| matches!(c, Outcome::Win(d) if *d < distance)));
+                        assert!(distance == 0 || children.contains(&Outcome::Win(distance - 1)));
+                    }
+                    Outcome::Draw => {
+                        assert!(children.contains(&Outcome::Draw));
+                        assert!(!children.iter().any(|c| matches!(c, Outcome::Loss(_))));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_index() {
+        for peices in 2..=5 {
+            for material in Material::all(peices) {
+                let size = material.table_size();
+                for idx in (0..size).step_by(size / 500 + 1) {
+                    let Some(bitboard) = material.position(idx) else {
+                        continue;
+                    };
+                    assert_eq!(Material::of(&bitboard), material);
+                    assert_eq!(material.index(&bitboard), idx);
+                }
+            }
+        }
+
+        // Men are never stored on the row they would be crowned on
+        let material = Material {
+            black_men: 1,
+            black_kings: 0,
+            white_men: 1,
+            white_kings: 0,
+        };
+        assert_eq!(material.table_size(), 28 * 28);
+    }
+
+    #[test]
+    fn test_generate() {
+        let tablebase = Tablebase::generate(3, |_, _| ()).unwrap();
+        let pos = |s: &str| Position::from_str(s).unwrap();
+
+        // Black can jump whites only man straight away
+        let mut board = Board::empty();
+        board[pos("d4")].take_ownership(Player::Black);
+        board[pos("d4")].promote();
+        board[pos("c3")].take_ownership(Player::White);
+        assert_eq!(
+            tablebase.probe(&board, Player::Black),
+            Some(Outcome::Win(1))
+        );
+        // But with White to move the man runs to be crowned
+        assert_eq!(tablebase.probe(&board, Player::White), Some(Outcome::Draw));
+
+        // Too many peices
+        board[pos("h8")].take_ownership(Player::White);
+        board[pos("a1")].take_ownership(Player::Black);
+        assert_eq!(tablebase.probe(&board, Player::Black), None);
+
+        check_tables(&tablebase);
+    }
+
+    #[test]
+    fn test_four_peices() {
+        // Two kings each, along with every table it leads to
+        let mut tablebase = Tablebase {
+            tables: BTreeMap::new(),
+            max_peices: 0,
+        };
+        let material = Material {
+            black_men: 0,
+            black_kings: 2,
+            white_men: 0,
+            white_kings: 2,
+        };
+        tablebase.generate_material(material).unwrap();
+        check_tables(&tablebase);
+
+        // Two kings beat one, even from the double corner
+        let (board, _) = Board::from_fen(Variant::English, "B:WK1:BK18,K19").unwrap();
+        assert!(matches!(
+            tablebase.probe(&board, Player::Black),
+            Some(Outcome::Win(_))
+        ));
+        assert!(matches!(
+            tablebase.probe(&board, Player::White),
+            Some(Outcome::Loss(_))
+        ));
+
+        // But two kings each is a draw
+        let (board, _) = Board::from_fen(Variant::English, "B:WK1,K5:BK28,K32").unwrap();
+        assert_eq!(tablebase.probe(&board, Player::Black), Some(Outcome::Draw));
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let tablebase = Tablebase::generate(2, |_, _| ()).unwrap();
+        let path = env::temp_dir().join(format!("checkers-tablebase-{}", std::process::id()));
+
+        tablebase.save(&path).unwrap();
+        let loaded = Tablebase::load(&path).unwrap();
+        assert_eq!(loaded.tables, tablebase.tables);
+        assert_eq!(loaded.max_peices(), 2);
+
+        std::fs::write(&path, b"CKTB\x02\x02\x00").unwrap();
+        assert!(Tablebase::load(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,180 @@
+//! A fixed size hash table of previously searched positions, so positions
+//! reached through a different order of moves don't need to be searched again.
+//! The table is shared between search threads without locking. Each slot
+//! stores the key xored with the entry, so a slot torn by two threads writing
+//! at once no longer matches its key and is ignored.
+
+use std::{
+    mem::size_of,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+/// How the stored score relates to the true score of the position
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Bound {
+    /// The score is exact
+    Exact,
+    /// The true score is at least the stored score
+    Lower,
+    /// The true score is at most the stored score
+    Upper,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// The depth the position was searched to
+    pub depth: u8,
+    pub bound: Bound,
+    pub score: i32,
+    /// The index of the best move in the positions list of legal moves
+    pub best_move: Option<u8>,
+}
+
+impl Entry {
+    const NO_MOVE: u64 = 0xFF;
+
+    /// Pack the entry into a single 64 bit word
+    fn pack(&self) -> u64 {
+        let bound = match self.bound {
+            Bound::Exact => 0,
+            Bound::Lower => 1,
+            Bound::Upper => 2,
+        };
+        let best_move = self.best_move.map_or(Self::NO_MOVE, u64::from);
+
+        (self.score as u32 as u64)
+            | (u64::from(self.depth) << 32)
+            | (bound << 40)
+            | (best_move << 48)
+    }
+
+    fn unpack(data: u64) -> Self {
+        let bound = match (data >> 40) & 0x3 {
+            0 => Bound::Exact,
+            1 => Bound::Lower,
+            _ => Bound::Upper,
+        };
+        let best_move = (data >> 48) & 0xFF;
+
+        Self {
+            depth: (data >> 32) as u8,
+            bound,
+            score: data as u32 as i32,
+            best_move: (best_move != Self::NO_MOVE).then_some(best_move as u8),
+        }
+    }
+}
+
+pub struct TranspositionTable {
+    /// Each slot holds the positions key xored with its packed entry, and the
+    /// packed entry
+    slots: Vec<[AtomicU64; 2]>,
+}
+
+impl TranspositionTable {
+    /// Create a table using roughly `size_mb` megabytes of memory
+    pub fn new(size_mb: usize) -> Self {
+        // Round down to a power of two so a slot can be found by masking
+        let slots = size_mb.max(1) * 1024 * 1024 / size_of::<[AtomicU64; 2]>();
+        Self {
+            slots: (0..1 << slots.ilog2())
+                .map(|_| [AtomicU64::new(0), AtomicU64::new(0)])
+                .collect(),
+        }
+    }
+
+    fn slot(&self, key: u64) -> usize {
+        key as usize & (self.slots.len() - 1)
+    }
+
+    pub fn probe(&self, key: u64) -> Option<Entry> {
+        let [checksum, data] = &self.slots[self.slot(key)];
+        let data = data.load(Ordering::Relaxed);
+        let checksum = checksum.load(Ordering::Relaxed);
+
+        (checksum ^ data == key && data != 0).then(|| Entry::unpack(data))
+    }
+
+    /// Store an entry, replacing whatever was in its slot unless it belongs
+    /// to the same position and was searched deeper
+    pub fn store(&self, key: u64, entry: Entry) {
+        if self
+            .probe(key)
+            .is_some_and(|stored| stored.depth > entry.depth)
+        {
+            return;
+        }
+
+        let [checksum, data] = &self.slots[self.slot(key)];
+        let packed = entry.pack();
+        checksum.store(key ^ packed, Ordering::Relaxed);
+        data.store(packed, Ordering::Relaxed);
+    }
+
+    pub fn clear(&self) {
+        for slot in &self.slots {
+            slot[0].store(0, Ordering::Relaxed);
+            slot[1].store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Bound, Entry, TranspositionTable};
+
+    #[test]
+    fn test_store_and_probe() {
+        let table = TranspositionTable::new(1);
+        let entry = Entry {
+            depth: 7,
+            bound: Bound::Lower,
+            score: -999_990,
+            best_move: Some(3),
+        };
+
+        assert_eq!(table.probe(42), None);
+        table.store(42, entry);
+        assert_eq!(table.probe(42), Some(entry));
+
+        // Shallower searches of the same position don't replace deeper ones
+        let shallow = Entry {
+            depth: 2,
+            best_move: None,
+            ..entry
+        };
+        table.store(42, shallow);
+        assert_eq!(table.probe(42), Some(entry));
+
+        table.clear();
+        assert_eq!(table.probe(42), None);
+    }
+
+    #[test]
+    fn test_shared_between_threads() {
+        let table = TranspositionTable::new(1);
+        let entry = |key: u64| Entry {
+            depth: (key % 64) as u8,
+            bound: Bound::Exact,
+            score: (key >> 40) as i32,
+            best_move: Some((key % 7) as u8),
+        };
+
+        // Threads fighting over the same slots never see another positions
+        // entry, or a mix of two entries
+        std::thread::scope(|scope| {
+            for thread in 0..4 {
+                let table = &table;
+                scope.spawn(move || {
+                    for i in 0..10_000u64 {
+                        let key = (i * 4 + thread) << 40 | (i % 16);
+                        table.store(key, entry(key));
+                        if let Some(found) = table.probe(key) {
+                            assert_eq!(found, entry(key));
+                        }
+                    }
+                });
+            }
+        });
+    }
+}
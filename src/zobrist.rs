@@ -0,0 +1,64 @@
+//! Zobrist keys used to hash board positions. Every combination of tile,
+//! owner and peice kind has a random 64 bit key, and a position is hashed by
+//! xoring together the keys of every occupied tile. Moving a peice only needs
+//! the keys of the tiles it touched to be xored in or out.
+
+use crate::{
+    board::MAX_BOARD_SIZE,
+    player::Player,
+    tile::{Tile, TileKind},
+};
+
+const MAX_TILES: usize = MAX_BOARD_SIZE * MAX_BOARD_SIZE;
+
+/// Generate the next number from a splitmix64 sequence, returning the new
+/// state and the random number
+const fn splitmix64(state: u64) -> (u64, u64) {
+    let state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    (state, z ^ (z >> 31))
+}
+
+/// Keys for each tile, indexed by black man, black king, white man, white king
+const PEICE_KEYS: [[u64; 4]; MAX_TILES] = {
+    let mut keys = [[0; 4]; MAX_TILES];
+    let mut state = 0x636B_6572_735F_7273;
+    let mut tile = 0;
+    while tile < MAX_TILES {
+        let mut peice = 0;
+        while peice < 4 {
+            let (next_state, key) = splitmix64(state);
+            state = next_state;
+            keys[tile][peice] = key;
+            peice += 1;
+        }
+        tile += 1;
+    }
+    keys
+};
+
+/// Xored into the hash when it is white to move
+const WHITE_TO_MOVE: u64 = splitmix64(0x7768_6974_6520_746F).1;
+
+/// The key for a peice on the tile at `idx`, empty tiles have no key
+pub fn tile_key(idx: usize, tile: &Tile) -> u64 {
+    let peice = match (tile.occupied_by, tile.kind()) {
+        (None, _) => return 0,
+        (Some(Player::Black), TileKind::Normal) => 0,
+        (Some(Player::Black), TileKind::King) => 1,
+        (Some(Player::White), TileKind::Normal) => 2,
+        (Some(Player::White), TileKind::King) => 3,
+    };
+
+    PEICE_KEYS[idx][peice]
+}
+
+/// The key for the player whose turn it is
+pub fn side_key(player: Player) -> u64 {
+    match player {
+        Player::Black => 0,
+        Player::White => WHITE_TO_MOVE,
+    }
+}